@@ -28,14 +28,13 @@ impl<'a> Iterator for AnnotatedStringIterator<'a>{
             .annotated_string
             .annotations
             .iter()
-            .filter(|annotation|{
-                //filter removes all annotation fir which closure does not return true
+            .rfind(|annotation|{
+                //for multiple overlapping annotations , take the last one, useful during syntax highlighting
                 annotation.start <= self.current_idx
                     && annotation.end > self.current_idx
                     //checking annotation is active, boundaries of annotation thus include byte_start_idx and exclude part at end_idx
 
             })
-            .last()//for multiple overlapping annotations , take the last one, useful during syntax highlighting
             {
                 let end_idx = min(annotation.end,
                 self.annotated_string.string.len());
@@ -1,5 +1,5 @@
 use std::{
-    cmp::{max, min},
+    cmp::min,
     fmt::{self, Display},
 };
 use crate::prelude::*;
@@ -37,6 +37,11 @@ impl AnnotatedString {
         });
     } //this allows adding an annotation to the string
 
+    pub fn prepend(&mut self, string: &str, annotation_type: AnnotationType) {
+        self.replace(0, 0, string); //shifts the existing annotations to the right
+        self.add_annotation(annotation_type, 0, string.len());
+    } //used to put the line number gutter in front of a line
+
     pub fn truncate_left_until(&mut self, untill: ByteIdx){
         self.replace(0, untill, "");
 
@@ -68,59 +73,33 @@ impl AnnotatedString {
             return;
         } // no length difference , nothing to do
 
+        let new_end = start.saturating_add(new_string.len()); //end of the replacement in the new string
+        //moves a byte index behind the replaced range by the difference in length
+        let shift = |idx: ByteIdx| {
+            if shortened {
+                idx.saturating_sub(len_difference)
+            } else {
+                idx.saturating_add(len_difference)
+            }
+        };
         self.annotations.iter_mut().for_each(|annotation| {
             //line gets a mutable reference to each annotation and performs below code, meaning items in self.annotations are changed without being copied or cloned
             annotation.start = if annotation.start >= end {
-                //case 1: start of annotation beyond end of insertion point
-                //here we need to move the annotation to right (if insert was bigger than removal) or to left if otherwise
-
-                //for annotation starting after replaced range we move the start index by difference in length
-                if shortened {
-                    annotation.start.saturating_sub(len_difference)
-                } else {
-                    annotation.start.saturating_add(len_difference)
-                }
-            } else if annotation.start >= start {
-                //case 2: start of annotation not beyound end of insertion but beyound start
-                //starts in middle
-                //here try to move the annotation to start accordingly , but at most to the boundaries
-                //move start index by dif in len , constrained to beginning or end of replaced range
-
-                if shortened {
-                    max(
-                        start,
-                        annotation.start.saturating_sub(len_difference),
-                    )
-                } else {
-                    min(
-                        end,
-                        annotation.start.saturating_add(len_difference),
-                    )
-                }
+                //case 1: start of annotation beyond end of replaced range, move it along with the text behind it
+                shift(annotation.start)
+            } else if annotation.start > start {
+                //case 2: starts in the middle of the replaced range, the annotation then covers the whole replacement
+                start
             } else {
                 annotation.start
-                //case 3 : start was before insertion point nothing to do
+                //case 3 : start was before replaced range nothing to do
             };
-            annotation.end = if annotation.end >= end {
+            annotation.end = if annotation.end >= end && annotation.end > start {
                 //for annotations ending after replaced range, we move the endindex by difference in length
-                if shortened {
-                    annotation.end.saturating_sub(len_difference)
-                } else {
-                    annotation.end.saturating_add(len_difference)
-                }
-            } else if annotation.end >= start {
-                // For annotations ending within the replaced range, we move the end index by the difference in length, constrained to the beginning or end of the replaced range.
-                if shortened {
-                    max(
-                        start,
-                        annotation.end.saturating_sub(len_difference),
-                    )
-                } else {
-                    min(
-                        end,
-                        annotation.end.saturating_add(len_difference),
-                    )
-                }
+                shift(annotation.end)
+            } else if annotation.end > start {
+                // annotations ending within the replaced range end with the replacement, never inside of a character
+                new_end
             } else {
                 annotation.end
             }//counter part for end of annotation
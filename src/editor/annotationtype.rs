@@ -10,5 +10,8 @@ pub enum AnnotationType{
     LifeTimeSpecifier,
     Comment,
     String,
-    
+    Selection,
+    LineNumber,
+    CurrentLineNumber,
+
 }
\ No newline at end of file
@@ -0,0 +1,6 @@
+// Storage behind the clipboard. Implement this to connect PikaNote to another clipboard,
+// e.g. the system clipboard or a terminal escape sequence like OSC 52.
+pub trait ClipboardBackend {
+    fn set(&mut self, text: &str);
+    fn get(&self) -> Option<String>;
+}
@@ -0,0 +1,29 @@
+mod clipboardbackend;
+pub use clipboardbackend::ClipboardBackend;
+mod register;
+use register::Register;
+
+// Clipboard shared by everything in the editor, the actual storage is delegated to a backend
+pub struct Clipboard {
+    backend: Box<dyn ClipboardBackend>,
+}
+
+impl Default for Clipboard {
+    fn default() -> Self {
+        Self::new(Box::<Register>::default())
+    }
+}
+
+impl Clipboard {
+    pub fn new(backend: Box<dyn ClipboardBackend>) -> Self {
+        Self { backend }
+    }
+
+    pub fn set(&mut self, text: &str) {
+        self.backend.set(text);
+    }
+
+    pub fn get(&self) -> Option<String> {
+        self.backend.get()
+    }
+}
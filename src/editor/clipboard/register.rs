@@ -0,0 +1,17 @@
+use super::ClipboardBackend;
+
+// In-process clipboard: the text lives only as long as the editor runs
+#[derive(Default)]
+pub struct Register {
+    content: Option<String>,
+}
+
+impl ClipboardBackend for Register {
+    fn set(&mut self, text: &str) {
+        self.content = Some(text.to_string());
+    }
+
+    fn get(&self) -> Option<String> {
+        self.content.clone()
+    }
+}
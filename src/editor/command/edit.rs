@@ -4,12 +4,20 @@ use crossterm::event::{
     }, KeyEvent, KeyModifiers
 };
 
-#[derive(Clone,Copy)]
+#[derive(Clone)]
 pub enum Edit{
     Insert(char),
+    InsertText(String),//a whole block of text, e.g. pasted into the terminal
     InsertNewLine,//Renamed Enter
     Delete,
     DeleteBackward,//Renamed Backspace
+    DeleteWord,
+    DeleteWordBackward,
+    Undo,
+    Redo,
+    Cut,
+    Copy,
+    Paste,
 }// this aims to edit something
 
 impl TryFrom<KeyEvent> for Edit{
@@ -24,6 +32,13 @@ impl TryFrom<KeyEvent> for Edit{
             (Enter, KeyModifiers::NONE) => Ok(Self::InsertNewLine),
             (Backspace, KeyModifiers::NONE) => Ok(Self::DeleteBackward),
             (Delete, KeyModifiers::NONE) => Ok(Self::Delete),
+            (Backspace, KeyModifiers::CONTROL) => Ok(Self::DeleteWordBackward),
+            (Delete, KeyModifiers::CONTROL) => Ok(Self::DeleteWord),
+            (Char('z'), KeyModifiers::CONTROL) => Ok(Self::Undo),
+            (Char('y'), KeyModifiers::CONTROL) => Ok(Self::Redo),
+            (Char('x'), KeyModifiers::CONTROL) => Ok(Self::Cut),
+            (Char('c'), KeyModifiers::CONTROL) => Ok(Self::Copy),
+            (Char('v'), KeyModifiers::CONTROL) => Ok(Self::Paste),
             _ => Err(format!(
                 "Unsupported key code {:?} with modifiers {:?}",
                 event.code, event.modifiers
@@ -1,5 +1,5 @@
 use crate::prelude::*;
-use crossterm::event::Event;
+use crossterm::event::{Event, KeyModifiers};
 use std::convert::TryFrom;
 mod movecommand;
 pub use movecommand::Move;
@@ -8,9 +8,10 @@ pub use system::System;
 mod edit;
 pub use edit::Edit;
 
-#[derive(Copy,Clone)]
+#[derive(Clone)]
 pub enum Command{
     Move(Move),
+    Select(Move),//movement while holding shift, extends the selection
     Edit(Edit),
     System(System),
 }
@@ -22,10 +23,17 @@ impl TryFrom<Event> for Command{
         match event{
             Event::Key(key_event)=>Edit::try_from(key_event)
                 .map(Command::Edit)
-                .or_else(|_| Move::try_from(key_event).map(Command::Move))
+                .or_else(|_| Move::try_from(key_event).map(|move_command|{
+                    if key_event.modifiers.contains(KeyModifiers::SHIFT){
+                        Command::Select(move_command)
+                    }else{
+                        Command::Move(move_command)
+                    }
+                }))
                 .or_else(|_| System::try_from(key_event).map(Command::System))
                 .map_err(|_err|format!("Event not supported: {key_event:?}")),
 
+            Event::Paste(text)=>Ok(Self::Edit(Edit::InsertText(text))),
             Event::Resize(width_u16,height_u16 )=>Ok(Self::System(System::Resize(Size{
                 height: height_u16 as usize,
                 width: width_u16 as usize,
@@ -14,6 +14,12 @@ pub enum Move{
     Left,
     Right,
     Down,
+    WordLeft,
+    WordRight,
+    StartOfFile,
+    EndOfFile,
+    PreviousParagraph,
+    NextParagraph,
 }
 impl TryFrom<KeyEvent> for Move{
     type Error = String;
@@ -21,6 +27,8 @@ impl TryFrom<KeyEvent> for Move{
         let KeyEvent{
             code,modifiers,..
         }=event;
+        //shift only turns movement into selection, which is decided by the caller
+        let modifiers = modifiers.difference(KeyModifiers::SHIFT);
         if modifiers==KeyModifiers::NONE{
             match code{
                 Up => Ok(Self::Up),
@@ -33,6 +41,16 @@ impl TryFrom<KeyEvent> for Move{
                 End => Ok(Self::EndOfLine),
                 _ => Err(format!("Unsupported code: {code:?}")),
             }
+        }else if modifiers==KeyModifiers::CONTROL{
+            match code{
+                Left => Ok(Self::WordLeft),
+                Right => Ok(Self::WordRight),
+                Home => Ok(Self::StartOfFile),
+                End => Ok(Self::EndOfFile),
+                Up => Ok(Self::PreviousParagraph),
+                Down => Ok(Self::NextParagraph),
+                _ => Err(format!("Unsupported CONTROL+{code:?} combination")),
+            }
         }else{
             Err(format!(
                 "Unsupported key code {code:?} or modifiers {modifiers:?}"
@@ -1,4 +1,5 @@
 use crate::prelude::*;
+use super::super::{SearchOption, SplitDirection};
 use crossterm::event::{
     KeyCode::{self, Char},
     KeyEvent, KeyModifiers,
@@ -9,9 +10,26 @@ use crossterm::event::{
 pub enum System{
     Save,
     Resize(Size),
+    Redraw,
     Quit,
     Dismiss,
-    Search
+    Search,
+    Replace,
+    GotoLine,
+    ToggleSearchOption(SearchOption),
+    ToggleLineNumbers,
+    ToggleSoftWrap,
+    ConvertLineEndings,
+    Open,
+    SwitchBuffer,
+    NextBuffer,
+    PreviousBuffer,
+    SplitPane(SplitDirection),
+    FocusNextPane,
+    ClosePane,
+    GrowPane,
+    ShrinkPane,
+    FindFile,
 }
 
 impl TryFrom<KeyEvent> for System{
@@ -25,8 +43,32 @@ impl TryFrom<KeyEvent> for System{
                 Char('q') => Ok(Self::Quit),
                 Char('s') => Ok(Self::Save),
                 Char('f')=> Ok(Self::Search),
+                Char('r')=> Ok(Self::Replace),
+                Char('g')=> Ok(Self::GotoLine),
+                Char('o')=> Ok(Self::Open),
+                Char('b')=> Ok(Self::SwitchBuffer),
+                Char('p')=> Ok(Self::FindFile),
+                Char('l')=> Ok(Self::Redraw),
                 _ => Err(format!("Unsupported CONTROL+{code:?} combination")),
             }
+        }else if modifiers == KeyModifiers::ALT {
+            match code {
+                Char('r') => Ok(Self::ToggleSearchOption(SearchOption::Regex)),
+                Char('c') => Ok(Self::ToggleSearchOption(SearchOption::SmartCase)),
+                Char('w') => Ok(Self::ToggleSearchOption(SearchOption::WholeWord)),
+                Char('l') => Ok(Self::ToggleLineNumbers),
+                Char('z') => Ok(Self::ToggleSoftWrap),
+                Char('e') => Ok(Self::ConvertLineEndings),
+                Char('n') => Ok(Self::NextBuffer),
+                Char('p') => Ok(Self::PreviousBuffer),
+                Char('s') => Ok(Self::SplitPane(SplitDirection::Horizontal)),
+                Char('v') => Ok(Self::SplitPane(SplitDirection::Vertical)),
+                Char('o') => Ok(Self::FocusNextPane),
+                Char('x') => Ok(Self::ClosePane),
+                Char('=') => Ok(Self::GrowPane),
+                Char('-') => Ok(Self::ShrinkPane),
+                _ => Err(format!("Unsupported ALT+{code:?} combination")),
+            }
         }else if modifiers == KeyModifiers::NONE && matches!(code, KeyCode::Esc) {
             Ok(Self::Dismiss)
 
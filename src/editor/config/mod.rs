@@ -0,0 +1,236 @@
+use std::{
+    env,
+    fs::read_to_string,
+    io::ErrorKind,
+    path::{Path, PathBuf},
+    sync::OnceLock,
+    time::Duration,
+};
+
+use crossterm::style::Color;
+use unicode_width::UnicodeWidthChar;
+
+use super::{expand_home, AnnotationType};
+
+mod value;
+use value::Value;
+
+const PROJECT_FILE_NAME: &str = ".pikanote.toml"; // looked for in the directory of the opened file and above
+
+// Names of the annotation types in [colors.<name>] sections
+const COLOR_NAMES: [(&str, AnnotationType); 13] = [
+    ("match", AnnotationType::Match),
+    ("selected_match", AnnotationType::SelectedMatch),
+    ("number", AnnotationType::Number),
+    ("keyword", AnnotationType::Keyword),
+    ("known_value", AnnotationType::KnownValue),
+    ("type", AnnotationType::Type),
+    ("char", AnnotationType::Char),
+    ("lifetime", AnnotationType::LifeTimeSpecifier),
+    ("comment", AnnotationType::Comment),
+    ("string", AnnotationType::String),
+    ("selection", AnnotationType::Selection),
+    ("line_number", AnnotationType::LineNumber),
+    ("current_line_number", AnnotationType::CurrentLineNumber),
+];
+
+static CONFIG: OnceLock<Config> = OnceLock::new();
+
+// Settings read from the config files at startup. They are global: the project files are those found for the first file
+// given on the command line, files of other projects opened in the same session are shown with the same settings.
+pub struct Config {
+    pub quit_times: u8,              // times Ctrl+Q has to be pressed to quit with unsaved changes
+    pub message_duration: Duration,  // how long messages stay in the message bar
+    pub filler: String,              // shown on rows below the end of the file
+    pub tab_character: char,         // shown in place of a tab
+    foreground: Vec<(AnnotationType, Color)>, // colors overriding the built in ones, later entries win
+    background: Vec<(AnnotationType, Color)>,
+    project_files: Vec<PathBuf>, // the project files which were read, nearest first
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            quit_times: 3,
+            message_duration: Duration::from_secs(5),
+            filler: "⚡".to_string(),
+            tab_character: ' ',
+            foreground: Vec::new(),
+            background: Vec::new(),
+            project_files: Vec::new(),
+        }
+    }
+}
+
+impl Config {
+    // The installed config, or the defaults if none was installed
+    pub fn get() -> &'static Self {
+        CONFIG.get_or_init(Self::default)
+    }
+
+    // Makes self the config returned by get, which only works once and before get was first called
+    pub fn install(self) {
+        let _ = CONFIG.set(self);
+    }
+
+    // Reads the user's config file and then the project files found walking up from dir, nearer ones overriding
+    // farther ones. Problems are described in the returned list, the settings they concern keep their previous value.
+    pub fn load(dir: &Path) -> (Self, Vec<String>) {
+        let mut config = Self::default();
+        let mut errors = Vec::new();
+        if let Some(path) = Self::user_file() {
+            config.apply_file(&path, &mut errors);
+        }
+        let project_files = Self::project_files(dir);
+        for path in project_files.iter().rev() {
+            config.apply_file(path, &mut errors);
+        }
+        config.project_files = project_files;
+        (config, errors)
+    }
+
+    // The project files applying to files in dir, nearest first
+    fn project_files(dir: &Path) -> Vec<PathBuf> {
+        dir.ancestors()
+            .map(|ancestor| ancestor.join(PROJECT_FILE_NAME))
+            .filter(|path| path.is_file())
+            .collect()
+    }
+
+    // Whether the project files read at startup are also those which apply to files in dir
+    pub fn applies_to(&self, dir: &Path) -> bool {
+        Self::project_files(dir) == self.project_files
+    }
+
+    // $XDG_CONFIG_HOME/pikanote/config.toml, falling back to ~/.config
+    fn user_file() -> Option<PathBuf> {
+        let config_dir = env::var("XDG_CONFIG_HOME")
+            .ok()
+            .filter(|dir| !dir.is_empty())
+            .unwrap_or_else(|| expand_home("~/.config"));
+        if config_dir.starts_with('~') {
+            return None; //no home directory
+        }
+        Some(Path::new(&config_dir).join("pikanote").join("config.toml"))
+    }
+
+    fn apply_file(&mut self, path: &Path, errors: &mut Vec<String>) {
+        match read_to_string(path) {
+            Ok(text) => self.apply(&text, path, errors),
+            Err(err) if err.kind() == ErrorKind::NotFound => {}
+            Err(err) => errors.push(format!("{err} ({})", display_path(path))),
+        }
+    }
+
+    fn apply(&mut self, text: &str, path: &Path, errors: &mut Vec<String>) {
+        let mut section = String::new();
+        for (idx, line) in text.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let result = if let Some(header) = line.strip_prefix('[') {
+                let name = header
+                    .split_once(']')
+                    .filter(|(_, rest)| rest.trim().is_empty() || rest.trim_start().starts_with('#'))
+                    .map(|(name, _)| name.trim());
+                //keys below an invalid header are not taken for keys of the previous section
+                section = name.unwrap_or(header).to_string();
+                name.map(|_| ()).ok_or_else(|| format!("invalid section header '{line}'"))
+            } else if let Some((key, value)) = line.split_once('=') {
+                Value::parse(value).and_then(|value| self.set(&section, key.trim(), value))
+            } else {
+                Err(format!("expected 'key = value', found '{line}'"))
+            };
+            if let Err(err) = result {
+                errors.push(format!("{err} ({}:{})", display_path(path), idx.saturating_add(1)));
+            }
+        }
+    }
+
+    fn set(&mut self, section: &str, key: &str, value: Value) -> Result<(), String> {
+        if let Some(name) = section.strip_prefix("colors.") {
+            let annotation_type = COLOR_NAMES
+                .iter()
+                .find(|(color_name, _)| *color_name == name)
+                .map(|&(_, annotation_type)| annotation_type)
+                .ok_or_else(|| format!("unknown color section '[{section}]'"))?;
+            let color = parse_color(&value)?;
+            match key {
+                "foreground" => self.foreground.push((annotation_type, color)),
+                "background" => self.background.push((annotation_type, color)),
+                _ => return Err(format!("unknown key '{key}' in [{section}], expected foreground or background")),
+            }
+            return Ok(());
+        }
+        if !section.is_empty() {
+            return Err(format!("unknown section '[{section}]'"));
+        }
+        match (key, value) {
+            ("quit_times", Value::Integer(times)) => {
+                self.quit_times = u8::try_from(times)
+                    .ok()
+                    .filter(|&times| times > 0)
+                    .ok_or_else(|| format!("quit_times must be between 1 and 255, not {times}"))?;
+            }
+            ("message_duration", Value::Integer(seconds)) => {
+                self.message_duration = u64::try_from(seconds)
+                    .ok()
+                    .filter(|&seconds| seconds > 0)
+                    .map(Duration::from_secs)
+                    .ok_or_else(|| format!("message_duration must be a positive number of seconds, not {seconds}"))?;
+            }
+            ("filler", Value::String(filler)) => self.filler = filler,
+            ("tab_character", Value::String(string)) => {
+                let mut chars = string.chars();
+                self.tab_character = chars
+                    .next()
+                    .filter(|&character| chars.next().is_none() && character.width() == Some(1))
+                    .ok_or_else(|| format!("tab_character must be a single character one column wide, not \"{string}\""))?;
+            }
+            ("quit_times" | "message_duration", value) => return Err(format!("{key} must be a number, not {value}")),
+            ("filler" | "tab_character", value) => return Err(format!("{key} must be a string, not {value}")),
+            _ => return Err(format!("unknown key '{key}'")),
+        }
+        Ok(())
+    }
+
+    pub fn foreground_of(&self, annotation_type: AnnotationType) -> Option<Color> {
+        Self::color_of(&self.foreground, annotation_type)
+    }
+
+    pub fn background_of(&self, annotation_type: AnnotationType) -> Option<Color> {
+        Self::color_of(&self.background, annotation_type)
+    }
+
+    fn color_of(colors: &[(AnnotationType, Color)], annotation_type: AnnotationType) -> Option<Color> {
+        colors
+            .iter()
+            .rev()
+            .find(|(colored, _)| *colored == annotation_type)
+            .map(|&(_, color)| color)
+    }
+}
+
+// The path with the home directory abbreviated to ~, to keep messages short
+fn display_path(path: &Path) -> String {
+    env::var("HOME")
+        .ok()
+        .and_then(|home| path.strip_prefix(home).ok())
+        .map_or_else(|| path.display().to_string(), |rest| format!("~/{}", rest.display()))
+}
+
+// Reads a color written as "#rrggbb"
+fn parse_color(value: &Value) -> Result<Color, String> {
+    let invalid = || format!("colors are written as \"#rrggbb\", not {value}");
+    let Value::String(string) = value else {
+        return Err(invalid());
+    };
+    let hex = string.strip_prefix('#').filter(|hex| hex.len() == 6).ok_or_else(invalid)?;
+    let component = |range| hex.get(range).and_then(|digits| u8::from_str_radix(digits, 16).ok()).ok_or_else(invalid);
+    Ok(Color::Rgb {
+        r: component(0..2)?,
+        g: component(2..4)?,
+        b: component(4..6)?,
+    })
+}
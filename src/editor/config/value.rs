@@ -0,0 +1,83 @@
+use std::fmt::{self, Display};
+
+// The value of a setting in a config file, a small subset of TOML
+pub enum Value {
+    String(String),
+    Integer(i64),
+    Boolean(bool),
+}
+
+impl Value {
+    // Parses the text right of the '=', which may end in a comment
+    pub fn parse(text: &str) -> Result<Self, String> {
+        let text = text.trim();
+        let (value, rest) = if let Some(quoted) = text.strip_prefix('"') {
+            let (string, rest) = Self::parse_basic_string(quoted)?;
+            (Self::String(string), rest)
+        } else if let Some(quoted) = text.strip_prefix('\'') {
+            let (string, rest) = quoted
+                .split_once('\'')
+                .ok_or_else(|| "missing closing ' of string".to_string())?;
+            (Self::String(string.to_string()), rest)
+        } else {
+            let bare = text.split_once('#').map_or(text, |(bare, _)| bare.trim_end());
+            return match bare {
+                "" => Err("missing value".to_string()),
+                "true" => Ok(Self::Boolean(true)),
+                "false" => Ok(Self::Boolean(false)),
+                _ => bare
+                    .replace('_', "")
+                    .parse()
+                    .map(Self::Integer)
+                    .map_err(|_| format!("invalid value '{bare}'")),
+            };
+        };
+        let rest = rest.trim_start();
+        if rest.is_empty() || rest.starts_with('#') {
+            Ok(value)
+        } else {
+            Err(format!("unexpected '{rest}' after string"))
+        }
+    }
+
+    // Reads a double quoted string up to its closing quote, returning it along with the text behind that quote
+    fn parse_basic_string(text: &str) -> Result<(String, &str), String> {
+        let mut string = String::new();
+        let mut chars = text.char_indices();
+        while let Some((idx, character)) = chars.next() {
+            match character {
+                '"' => return Ok((string, &text[idx.saturating_add(1)..])),
+                '\\' => {
+                    let escaped = match chars.next().map(|(_, escaped)| escaped) {
+                        Some('"') => '"',
+                        Some('\\') => '\\',
+                        Some('n') => '\n',
+                        Some('t') => '\t',
+                        Some('u') => {
+                            let digits: String = chars.by_ref().take(4).map(|(_, digit)| digit).collect();
+                            u32::from_str_radix(&digits, 16)
+                                .ok()
+                                .and_then(char::from_u32)
+                                .ok_or_else(|| format!("invalid escape \\u{digits}"))?
+                        }
+                        Some(other) => return Err(format!("invalid escape \\{other}")),
+                        None => break,
+                    };
+                    string.push(escaped);
+                }
+                _ => string.push(character),
+            }
+        }
+        Err("missing closing \" of string".to_string())
+    }
+}
+
+impl Display for Value {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::String(string) => write!(formatter, "\"{string}\""),
+            Self::Integer(integer) => write!(formatter, "{integer}"),
+            Self::Boolean(boolean) => write!(formatter, "{boolean}"),
+        }
+    }
+}
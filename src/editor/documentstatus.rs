@@ -1,5 +1,5 @@
 use crate::prelude::*;
-use super::FileType;
+use super::{FileType, LineEnding};
 #[derive(Default, PartialEq, Eq, Debug)] // Eq and partial eq allows comparisons  for checking status of rendering two cycles
 
 pub struct DocumentStatus {
@@ -8,6 +8,7 @@ pub struct DocumentStatus {
     pub is_modified: bool,
     pub file_name: String,
     pub file_type: FileType,
+    pub line_ending: LineEnding,
 }
 
 impl DocumentStatus {
@@ -31,4 +32,7 @@ impl DocumentStatus {
     pub fn file_type_to_string(&self)->String{
         self.file_type.to_string()
     }
+    pub fn line_ending_to_string(&self)->String{
+        self.line_ending.to_string()
+    }
 }
@@ -0,0 +1,124 @@
+use regex::Regex;
+use std::{iter::Peekable, str::Chars};
+
+// A pattern read from a .gitignore file
+pub struct IgnoreRule {
+    regex: Regex,
+    base: String,   // directory of the .gitignore file relative to the walked root, ending in '/' unless it is the root
+    anchored: bool, // patterns containing a slash match the path below base, others the file name at any depth
+    dir_only: bool, // patterns ending in a slash only match directories
+    negated: bool,  // patterns starting with '!' re-include what earlier patterns excluded
+}
+
+impl IgnoreRule {
+    // Parses a line of a .gitignore file, None for blank lines, comments and patterns that can't be understood
+    pub fn parse(line: &str, base: &str) -> Option<Self> {
+        let pattern = line.trim_end();
+        if pattern.is_empty() || pattern.starts_with('#') {
+            return None;
+        }
+        let (pattern, negated) = pattern
+            .strip_prefix('!')
+            .map_or((pattern, false), |pattern| (pattern, true));
+        let (pattern, dir_only) = pattern
+            .strip_suffix('/')
+            .map_or((pattern, false), |pattern| (pattern, true));
+        let anchored = pattern.contains('/');
+        let pattern = pattern.strip_prefix('/').unwrap_or(pattern);
+        let regex = Regex::new(&format!("^{}$", Self::glob_to_regex(pattern))).ok()?;
+        Some(Self {
+            regex,
+            base: base.to_string(),
+            anchored,
+            dir_only,
+            negated,
+        })
+    }
+
+    fn glob_to_regex(glob: &str) -> String {
+        let mut regex = String::new();
+        let mut chars = glob.chars().peekable();
+        while let Some(character) = chars.next() {
+            match character {
+                '*' if chars.peek() == Some(&'*') => {
+                    chars.next();
+                    if chars.peek() == Some(&'/') {
+                        chars.next();
+                        regex.push_str("(.*/)?"); // any number of directories, including none
+                    } else {
+                        regex.push_str(".*");
+                    }
+                }
+                '*' => regex.push_str("[^/]*"),
+                '?' => regex.push_str("[^/]"),
+                '[' => match Self::bracket_to_class(chars.clone()) {
+                    Some((class, rest)) => {
+                        regex.push_str(&class);
+                        chars = rest;
+                    }
+                    None => regex.push_str(r"\["), //without a closing bracket, it is an ordinary character
+                },
+                '\\' => {
+                    if let Some(escaped) = chars.next() {
+                        regex.push_str(&regex::escape(&escaped.to_string()));
+                    }
+                }
+                _ => regex.push_str(&regex::escape(&character.to_string())),
+            }
+        }
+        regex
+    }
+
+    // Translates a bracket expression, with chars just behind its '[', into a regex class.
+    // Characters are passed through so ranges keep working, only those the regex class syntax treats specially are escaped.
+    // Returns the class and the chars behind the closing ']', None if there is none.
+    fn bracket_to_class(mut chars: Peekable<Chars<'_>>) -> Option<(String, Peekable<Chars<'_>>)> {
+        let mut class = String::from("[");
+        if chars.next_if(|&character| character == '!' || character == '^').is_some() {
+            class.push('^');
+        }
+        let mut is_first = true; //a ']' right after the opening bracket is part of the set
+        let mut previous = None;
+        loop {
+            let character = chars.next()?;
+            match character {
+                ']' if !is_first => break,
+                '\\' => {
+                    let escaped = chars.next()?;
+                    class.push_str(&regex::escape(&escaped.to_string()));
+                }
+                //'&&', '~~' and '--' are set operators and '[' opens a nested class in regex classes
+                '[' | ']' | '&' | '~' => {
+                    class.push('\\');
+                    class.push(character);
+                }
+                '-' if is_first || previous == Some('-') || chars.peek() == Some(&']') => class.push_str(r"\-"),
+                _ => class.push(character),
+            }
+            is_first = false;
+            previous = Some(character);
+        }
+        class.push(']');
+        Some((class, chars))
+    }
+
+    pub const fn is_negated(&self) -> bool {
+        self.negated
+    }
+
+    // path is relative to the walked root, without a trailing slash
+    pub fn matches(&self, path: &str, is_dir: bool) -> bool {
+        if self.dir_only && !is_dir {
+            return false;
+        }
+        let Some(relative) = path.strip_prefix(&self.base) else {
+            return false;
+        };
+        if self.anchored {
+            self.regex.is_match(relative)
+        } else {
+            let name = relative.rsplit('/').next().unwrap_or(relative);
+            self.regex.is_match(name)
+        }
+    }
+}
@@ -0,0 +1,169 @@
+use super::fuzzy_score;
+use std::{
+    cmp::Reverse,
+    fs::{read_dir, read_to_string, DirEntry},
+    mem::take,
+    path::Path,
+    time::{Duration, Instant},
+};
+
+mod ignorerule;
+use ignorerule::IgnoreRule;
+
+const MAX_FILES: usize = 200_000; // bounds the memory used for huge trees
+const SKIPPED_DIRS: [&str; 2] = [".git", "target"];
+const WALK_STEP: Duration = Duration::from_millis(20); // how long a single step of the walk may block the editor
+
+// A directory the walk has entered but not yet finished
+struct WalkedDir {
+    relative_dir: String, // relative to the walked root, ending in '/' unless it is the root
+    entries: Vec<DirEntry>, // not yet visited, the next one last
+    inherited_rules: usize, // how many ignore rules applied before the directory was entered
+}
+
+// The files below a directory, ranked by how well they match a query.
+// The directory is walked in steps, so the editor stays responsive in huge trees while the list fills up.
+#[derive(Default)]
+pub struct FileFinder {
+    paths: Vec<String>, // relative to the walked directory, in walking order
+    matches: Vec<(usize, usize)>, // indices of the matching paths with their scores, best first
+    query: String,
+    walking: Vec<WalkedDir>, // the directories being walked, innermost last
+    rules: Vec<IgnoreRule>,  // the rules of the .gitignore files of the directories being walked
+    truncated: bool,         // the walk stopped at MAX_FILES
+}
+
+impl FileFinder {
+    // Starts collecting the files below root, leaving out what its .gitignore files exclude
+    pub fn new(root: &Path) -> Self {
+        let mut file_finder = Self::default();
+        file_finder.enter(root, String::new());
+        file_finder
+    }
+
+    fn enter(&mut self, dir: &Path, relative_dir: String) {
+        //rules of a .gitignore file apply to its directory and everything below
+        let inherited_rules = self.rules.len();
+        if let Ok(contents) = read_to_string(dir.join(".gitignore")) {
+            self.rules.extend(
+                contents
+                    .lines()
+                    .filter_map(|line| IgnoreRule::parse(line, &relative_dir)),
+            );
+        }
+        let mut entries: Vec<_> = read_dir(dir)
+            .map(|entries| entries.filter_map(Result::ok).collect())
+            .unwrap_or_default();
+        entries.sort_by_key(|entry| Reverse(entry.file_name()));
+        self.walking.push(WalkedDir {
+            relative_dir,
+            entries,
+            inherited_rules,
+        });
+    }
+
+    // Continues the walk for a short while, adding the files found to the matches. Returns whether it has to be continued.
+    pub fn walk_step(&mut self) -> bool {
+        let deadline = Instant::now().checked_add(WALK_STEP);
+        let first_new = self.paths.len();
+        while let Some(dir) = self.walking.last_mut() {
+            if self.paths.len() >= MAX_FILES {
+                self.truncated = true;
+                self.walking.clear();
+                break;
+            }
+            if deadline.is_some_and(|deadline| Instant::now() >= deadline) {
+                break;
+            }
+            let Some(entry) = dir.entries.pop() else {
+                self.rules.truncate(dir.inherited_rules);
+                self.walking.pop();
+                continue;
+            };
+            let (Ok(name), Ok(file_type)) = (entry.file_name().into_string(), entry.file_type()) else {
+                continue;
+            };
+            let is_dir = file_type.is_dir(); //symlinks are not followed, so there are no cycles
+            let path = format!("{}{name}", dir.relative_dir);
+            if (is_dir && SKIPPED_DIRS.contains(&name.as_str())) || Self::is_ignored(&self.rules, &path, is_dir) {
+                continue;
+            }
+            if is_dir {
+                self.enter(&entry.path(), format!("{path}/"));
+            } else {
+                self.paths.push(path);
+            }
+        }
+        //the new matches are merged into the ranked ones, a stable sort merges the two sorted runs in linear time
+        let new_matches = self.score(first_new..self.paths.len());
+        let mut matches = take(&mut self.matches);
+        matches.extend(new_matches);
+        matches.sort_by_key(|&scored| self.rank_key(scored));
+        self.matches = matches;
+        self.is_walking()
+    }
+
+    // The last matching rule decides
+    fn is_ignored(rules: &[IgnoreRule], path: &str, is_dir: bool) -> bool {
+        rules
+            .iter()
+            .rev()
+            .find(|rule| rule.matches(path, is_dir))
+            .is_some_and(|rule| !rule.is_negated())
+    }
+
+    pub fn is_walking(&self) -> bool {
+        !self.walking.is_empty()
+    }
+
+    // Whether files were left out because there were too many
+    pub const fn is_truncated(&self) -> bool {
+        self.truncated
+    }
+
+    pub fn file_count(&self) -> usize {
+        self.paths.len()
+    }
+
+    pub fn match_count(&self) -> usize {
+        self.matches.len()
+    }
+
+    // Ranks the paths for query. When the query was extended, only the previous matches can still match.
+    pub fn update(&mut self, query: &str) {
+        let candidates: Vec<usize> = if query.starts_with(self.query.as_str()) {
+            self.matches.iter().map(|&(idx, _)| idx).collect()
+        } else {
+            (0..self.paths.len()).collect()
+        };
+        self.query = query.to_string();
+        self.matches = self.score(candidates);
+    }
+
+    // The candidates matching the query with their scores, best first
+    fn score(&self, candidates: impl IntoIterator<Item = usize>) -> Vec<(usize, usize)> {
+        let mut scored: Vec<(usize, usize)> = candidates
+            .into_iter()
+            .filter_map(|idx| {
+                let path = self.paths.get(idx)?;
+                fuzzy_score(&self.query, path).map(|score| (idx, score))
+            })
+            .collect();
+        scored.sort_unstable_by_key(|&scored| self.rank_key(scored));
+        scored
+    }
+
+    //among equally good matches, shorter paths come first
+    fn rank_key(&self, (idx, score): (usize, usize)) -> (Reverse<usize>, usize, usize) {
+        (Reverse(score), self.paths.get(idx).map_or(0, String::len), idx)
+    }
+
+    // The best count matches
+    pub fn best_matches(&self, count: usize) -> Vec<String> {
+        self.matches
+            .iter()
+            .take(count)
+            .filter_map(|&(idx, _)| self.paths.get(idx).cloned())
+            .collect()
+    }
+}
@@ -0,0 +1,30 @@
+// Scores how well query matches candidate as a case-insensitive subsequence, or None if it doesn't match at all.
+// Higher is better: consecutive characters and matches at the start of words count extra.
+pub fn fuzzy_score(query: &str, candidate: &str) -> Option<usize> {
+    let mut query_chars = query.chars().flat_map(char::to_lowercase).peekable();
+    let mut score: usize = 0;
+    let mut prev_matched = false;
+    let mut prev_char: Option<char> = None;
+    for character in candidate.chars() {
+        let Some(&query_char) = query_chars.peek() else {
+            break;
+        };
+        let is_match = character.to_lowercase().eq(std::iter::once(query_char));
+        if is_match {
+            query_chars.next();
+            score = score.saturating_add(1);
+            if prev_matched {
+                score = score.saturating_add(4);
+            }
+            let is_word_start = prev_char.is_none_or(|prev_char| {
+                !prev_char.is_alphanumeric() || (prev_char.is_lowercase() && character.is_uppercase())
+            });
+            if is_word_start {
+                score = score.saturating_add(3);
+            }
+        }
+        prev_matched = is_match;
+        prev_char = Some(character);
+    }
+    query_chars.peek().is_none().then_some(score)
+}
@@ -0,0 +1,23 @@
+use crate::prelude::*;
+use super::TextFragment;
+
+// A grapheme boundary within a line, together with the number of graphemes and columns in front of it
+#[derive(Clone, Copy, Default, Debug)]
+#[allow(clippy::struct_field_names)]//named after the index types they hold
+pub struct Boundary {
+    pub byte_idx: ByteIdx,
+    pub grapheme_idx: GraphemeIdx,
+    pub col_idx: ColIdx,
+}
+
+impl Boundary {
+    // The boundary behind the given fragment, which has to start at this boundary
+    pub fn after(self, fragment: &TextFragment) -> Self {
+        debug_assert_eq!(self.byte_idx, fragment.start);
+        Self {
+            byte_idx: fragment.end(),
+            grapheme_idx: self.grapheme_idx.saturating_add(1),
+            col_idx: self.col_idx.saturating_add(fragment.rendered_width.into()),
+        }
+    }
+}
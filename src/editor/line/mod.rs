@@ -1,80 +1,153 @@
 use crate::prelude::*;
 use std::{
+    cell::{OnceCell, RefCell},
     cmp::min,
     fmt::{self, Display},
+    iter,
     ops::{Deref, Range},
 };
+mod boundary;
 mod graphemewidth;
 mod textfragment;
+use boundary::Boundary;
 use graphemewidth::GraphemeWidth;
 use textfragment::TextFragment;
-use unicode_segmentation::UnicodeSegmentation;
+use unicode_segmentation::{GraphemeCursor, UnicodeSegmentation};
 use unicode_width::UnicodeWidthStr;
 
 use super::AnnotatedString;
 use super::Annotation;
+use super::Config;
+use super::SearchQuery;
 
+const CHUNK_LEN: ByteIdx = 1024; // approximate distance between two indexed grapheme boundaries
 
+// Grapheme metadata isn't stored per grapheme. Instead a grapheme boundary is indexed about every CHUNK_LEN bytes once it is first needed,
+// lookups segment only the chunk they fall into and edits re-index only the chunks they touch, so huge lines stay cheap.
 #[derive(Default, Clone)]
 pub struct Line {
-    fragments: Vec<TextFragment>,
     string: String, //store entire string
+    boundaries: OnceCell<Vec<Boundary>>, // starts at the start and ends at the end of the line
+    wrapped: RefCell<Option<(ColIdx, Vec<Range<GraphemeIdx>>)>>, // the rows of the last wrap and the width they were wrapped to
 }
 
 impl Line {
     pub fn from(line_str: &str) -> Self {
         debug_assert!(line_str.is_empty() || line_str.lines().count() == 1);
-        let fragments = Self::str_to_fragments(line_str);
         Self {
-            fragments,
             string: String::from(line_str),
+            boundaries: OnceCell::new(),
+            wrapped: RefCell::new(None),
         }
     }
 
-    fn str_to_fragments(line_str: &str) -> Vec<TextFragment> {
-        line_str
-            .grapheme_indices(true) //turn string to grapheme
-            .map(|(byte_idx, grapheme)| {
-                // turn grapheme in to tuple with two elements and then destructure them
-                let (replacement, rendered_width) = Self::get_replacement_character(grapheme)
-                    .map_or_else(
-                        || {
-                            let unicode_width = grapheme.width();
-                            let rendered_width = match unicode_width {
-                                0 | 1 => GraphemeWidth::Half,
-                                _ => GraphemeWidth::Full,
-                            };
-                            (None, rendered_width)
-                        },
-                        |replacement| (Some(replacement), GraphemeWidth::Half),
-                    );
+    fn fragment(grapheme: &str, start: ByteIdx) -> TextFragment<'_> {
+        // turn grapheme in to tuple with two elements and then destructure them
+        let (replacement, rendered_width) = Self::get_replacement_character(grapheme)
+            .map_or_else(
+                || {
+                    let unicode_width = grapheme.width();
+                    let rendered_width = match unicode_width {
+                        0 | 1 => GraphemeWidth::Half,
+                        _ => GraphemeWidth::Full,
+                    };
+                    (None, rendered_width)
+                },
+                |replacement| (Some(replacement), GraphemeWidth::Half),
+            );
+        TextFragment {
+            grapheme,
+            rendered_width,
+            replacement,
+            start,
+        }
+    }
 
-                TextFragment {
-                    grapheme: grapheme.to_string(),
-                    rendered_width,
-                    replacement,
-                    start: byte_idx, // store byte idx
-                } // construct a text fragment
+    // The fragments from the given boundary to the end of the line, each with the boundary it starts at
+    fn fragments_from(&self, from: Boundary) -> impl Iterator<Item = (Boundary, TextFragment<'_>)> {
+        self.string
+            .get(from.byte_idx..)
+            .unwrap_or_default()
+            .grapheme_indices(true)
+            .scan(from, move |at, (offset, grapheme)| {
+                let boundary = *at;
+                let fragment = Self::fragment(grapheme, from.byte_idx.saturating_add(offset));
+                *at = boundary.after(&fragment);
+                Some((boundary, fragment))
             })
-            .collect()
     }
-    fn rebuild_fragments(&mut self) {
-        //helper fxn to rebuild fragment and replace self.fragment as result
-        self.fragments = Self::str_to_fragments(&self.string);
+
+    fn boundaries(&self) -> &[Boundary] {
+        self.boundaries.get_or_init(|| {
+            let (mut boundaries, end) = self.index(Boundary::default(), self.string.len());
+            boundaries.push(end);
+            boundaries
+        })
+    }
+
+    // Segments the text from the boundary up to the grapheme boundary at byte_idx.
+    // Returns a boundary about every CHUNK_LEN bytes, starting with from, and the boundary at byte_idx.
+    fn index(&self, from: Boundary, byte_idx: ByteIdx) -> (Vec<Boundary>, Boundary) {
+        let mut boundaries = vec![from];
+        let mut end = from;
+        for (boundary, fragment) in self.fragments_from(from) {
+            if boundary.byte_idx >= byte_idx {
+                break;
+            }
+            if boundaries
+                .last()
+                .is_some_and(|last| boundary.byte_idx >= last.byte_idx.saturating_add(CHUNK_LEN))
+            {
+                boundaries.push(boundary);
+            }
+            end = boundary.after(&fragment);
+        }
+        (boundaries, end)
+    }
+
+    // The last indexed boundary whose key is at most value, lookups continue segmenting from there
+    fn chunk_start(&self, key: impl Fn(&Boundary) -> usize, value: usize) -> Boundary {
+        let boundaries = self.boundaries();
+        let idx = boundaries
+            .partition_point(|boundary| key(boundary) <= value)
+            .saturating_sub(1);
+        boundaries.get(idx).copied().unwrap_or_default()
+    }
+
+    fn end(&self) -> Boundary {
+        self.boundaries().last().copied().unwrap_or_default()
+    }
+
+    // The boundary in front of the grapheme, or the end of the line if there is no such grapheme
+    fn boundary_at_grapheme(&self, grapheme_idx: GraphemeIdx) -> Boundary {
+        self.fragments_from(self.chunk_start(|boundary| boundary.grapheme_idx, grapheme_idx))
+            .map(|(boundary, _)| boundary)
+            .find(|boundary| boundary.grapheme_idx == grapheme_idx)
+            .unwrap_or_else(|| self.end())
+    }
+
+    // The boundary in front of the grapheme which covers the column, or the end of the line if the column lies beyond it
+    fn boundary_at_col(&self, col_idx: ColIdx) -> Boundary {
+        self.fragments_from(self.chunk_start(|boundary| boundary.col_idx, col_idx))
+            .find(|(boundary, fragment)| {
+                boundary.col_idx.saturating_add(fragment.rendered_width.into()) > col_idx
+            })
+            .map_or_else(|| self.end(), |(boundary, _)| boundary)
     }
 
     fn get_replacement_character(for_str: &str) -> Option<char> {
         let width = for_str.width();
         match for_str {
             " " => None,
-            "\t" => Some(' '),
+            "\t" => Some(Config::get().tab_character),
             _ if width > 0 && for_str.trim().is_empty() => Some('␣'),
             _ if width == 0 => {
                 let mut chars = for_str.chars();
-                if let Some(ch) = chars.next() {
-                    if ch.is_control() && chars.next().is_none() {
-                        return Some('▯');
-                    }
+                if let Some(ch) = chars.next()
+                    && ch.is_control()
+                    && chars.next().is_none()
+                {
+                    return Some('▯');
                 }
                 Some('·')
             }
@@ -91,8 +164,7 @@ impl Line {
     // A grapheme can have a width of 2 columns.
     // Parameters:
     // - range: The range of columns to get the annotated string from.
-    // - query: The query to highlight in the annotated string.
-    // - selected_match: The selected match to highlight in the annotated string. This is only applied if the query is not empty.
+    // - annotations: The annotations of the whole line, they are clipped to the visible part.
 
     pub fn get_annotated_visible_substr(
         &self,
@@ -103,116 +175,216 @@ impl Line {
         if range.start >= range.end {
             return AnnotatedString::default();
         }
-        //create new annotated string
-        let mut result = AnnotatedString::from(&self.string);
-        //start with full string and  remove and replaec things untill arrive at destination
+        // only the graphemes overlapping the visible range are segmented, starting with the one covering its first column
+        let visible: Vec<_> = self
+            .fragments_from(self.boundary_at_col(range.start))
+            .take_while(|(boundary, _)| boundary.col_idx < range.end)
+            .collect();
+        let (Some((first, _)), Some((_, last))) = (visible.first(), visible.last()) else {
+            return AnnotatedString::default();
+        };
+        let window = first.byte_idx..last.end();
+        let mut result = AnnotatedString::from(self.string.get(window.clone()).unwrap_or_default());
 
-        // Apply annotations for this string
+        // Apply the annotations overlapping the visible part, relative to its start
         if let Some(annotations) = annotations {
             for annotation in annotations {
-                result.add_annotation(annotation.annotation_type, annotation.start, annotation.end);
-
-        
+                let start = annotation.start.max(window.start);
+                let end = annotation.end.min(window.end);
+                if start < end {
+                    result.add_annotation(
+                        annotation.annotation_type,
+                        start.saturating_sub(window.start),
+                        end.saturating_sub(window.start),
+                    );
+                }
             }
         }
-        //insert replacement characters and truncating if needed
+        //insert replacement characters and ellipses for partially visible graphemes
         // perform this backward as byte indices become off in case a replacement char has different width than original char
-        let mut fragment_start = self.width();
-        //result string contains original , raw string as read , its too long with no esc chars but is now annotated
-
-        for fragment in self.fragments.iter().rev() {
-            //iterate over all fragments from back to front
-            let fragment_end = fragment_start;
-            //to go to left keep the new fragment end as old fragment start
-            fragment_start =fragment_start.saturating_sub(fragment.rendered_width.into());
-            //new fragment start = prev fragment start reduced by grapheme width of current fragment
-            if fragment_start > range.end {
-                continue; // no processing needed if we havent reach visible range yet
+        for (boundary, fragment) in visible.iter().rev() {
+            let fragment_start = boundary.col_idx;
+            let fragment_end = fragment_start.saturating_add(fragment.rendered_width.into());
+            let start = fragment.start.saturating_sub(window.start);
+            let end = fragment.end().saturating_sub(window.start);
+            if fragment_end > range.end {
+                //clip right if fragment range partially visible
+                result.replace(start, end, "⋯");
+            } else if fragment_start < range.start {
+                // Fragment overlaps with the start of range: replace it with an ellipsis
+                result.replace(0, end, "⋯");
+            } else if let Some(replacement) = fragment.replacement {
+                // Fragment is fully within range: Apply replacement characters if appropriate
+                result.replace(start, end, &replacement.to_string());
             }
+        }
+        result
+    }
 
-            //clip right if fragment range partially visible
-            if fragment_start < range.end && fragment_end > range.end {
-                result.replace(fragment.start, self.string.len(), "⋯");
-                continue;
-            }
-            //here we truncate to right, replace entire right of string which is not visible with ellipses
-            else if fragment_start == fragment_end {
-                //truncate to right if we reach end of visible range
-                result.truncate_right_from(fragment.start);
-                continue;
-            }
+    // Splits the line into the grapheme ranges shown on each screen row when it is soft wrapped to the given width.
+    // Rows break after whitespace where possible, otherwise at the last grapheme that fits.
+    // A line whose last row is completely filled gets an additional empty row, so the caret after its end stays visible.
+    // The rows are kept until the line is edited, so wrapping it to the same width again is cheap.
+    pub fn wrap(&self, width: ColIdx) -> Vec<Range<GraphemeIdx>> {
+        if let Some((wrapped_width, rows)) = self.wrapped.borrow().as_ref()
+            && *wrapped_width == width
+        {
+            return rows.clone();
+        }
+        let rows = self.wrap_uncached(width);
+        *self.wrapped.borrow_mut() = Some((width, rows.clone()));
+        rows
+    }
 
-            //fragment end at start of range: remove entire left side of string
-            if fragment_end <= range.start {
-                result.truncate_left_until(fragment.start.saturating_add(fragment.grapheme.len()));
-                break; //End processing since all remaining fragments will be invisible.
-            } else if fragment_start < range.start && fragment_end > range.start {
-                // Fragment overlaps with the start of range: Remove the left side of the string and add an ellipsis
-                result.replace(
-                    0,
-                    fragment
-                        .start
-                        .saturating_add(fragment.grapheme.len()),
-                    "⋯",
-                );
-                break; //End processing since all remaining fragments will be invisible
+    fn wrap_uncached(&self, width: ColIdx) -> Vec<Range<GraphemeIdx>> {
+        let width = width.max(1);
+        let mut rows = Vec::new();
+        let mut row_start = Boundary::default();
+        let mut break_opportunity: Option<Boundary> = None;
+        let mut end = Boundary::default();
+        for (boundary, fragment) in self.fragments_from(Boundary::default()) {
+            end = boundary.after(&fragment);
+            while end.col_idx.saturating_sub(row_start.col_idx) > width
+                && boundary.grapheme_idx > row_start.grapheme_idx
+            {
+                let row_end = break_opportunity
+                    .filter(|break_boundary| break_boundary.grapheme_idx > row_start.grapheme_idx)
+                    .unwrap_or(boundary);
+                rows.push(row_start.grapheme_idx..row_end.grapheme_idx);
+                row_start = row_end;
+                break_opportunity = None;
             }
-            // Fragment is fully within range: Apply replacement characters if appropriate
-            if fragment_start >= range.start && fragment_end <= range.end {
-                if let Some(replacement) = fragment.replacement {
-                    let start = fragment.start;
-                    let end = start.saturating_add(fragment.grapheme.len());
-                    result.replace(start, end, &replacement.to_string());
-                }
+            if fragment.grapheme.trim().is_empty() {
+                break_opportunity = Some(end); //break after whitespace
             }
         }
-        result
+        rows.push(row_start.grapheme_idx..end.grapheme_idx);
+        if end.col_idx.saturating_sub(row_start.col_idx) >= width {
+            rows.push(end.grapheme_idx..end.grapheme_idx);
+        }
+        rows
+    }
+
+    // Returns the grapheme within range which is displayed at col, counted from the start of the range, or range.end if col lies beyond it.
+    pub fn grapheme_idx_at(&self, range: Range<GraphemeIdx>, col: ColIdx) -> GraphemeIdx {
+        let col_idx = self.width_until(range.start).saturating_add(col);
+        min(self.boundary_at_col(col_idx).grapheme_idx, range.end)
+    }
+
+    // Returns the end of the next word after from, skipping whitespace and punctuation. Returns the end of the line if there is none.
+    pub fn next_word_end(&self, from: GraphemeIdx) -> GraphemeIdx {
+        let from = self.grapheme_idx_to_byte_idx(from);
+        self.string
+            .get(from..)
+            .unwrap_or_default()
+            .split_word_bound_indices()
+            .find(|(_, word)| is_word(word))
+            .and_then(|(start, word)| {
+                self.byte_idx_to_grapheme_idx(from.saturating_add(start).saturating_add(word.len()))
+            })
+            .unwrap_or_else(|| self.grapheme_count())
+    }
+
+    // Returns the start of the word before from, skipping whitespace and punctuation. Returns the start of the line if there is none.
+    pub fn previous_word_start(&self, from: GraphemeIdx) -> GraphemeIdx {
+        let from = self.grapheme_idx_to_byte_idx(from);
+        self.string
+            .get(..from)
+            .unwrap_or_default()
+            .split_word_bound_indices()
+            .rev() //only the part in front of from is segmented
+            .find(|(_, word)| is_word(word))
+            .and_then(|(start, _)| self.byte_idx_to_grapheme_idx(start))
+            .unwrap_or(0)
     }
 
     pub fn grapheme_count(&self) -> GraphemeIdx {
-        self.fragments.len()
+        self.end().grapheme_idx
     }
 
     pub fn width_until(&self, grapheme_idx: GraphemeIdx) -> ColIdx {
-        self.fragments
-            .iter()
-            .take(grapheme_idx)
-            .map(|fragment| match fragment.rendered_width {
-                GraphemeWidth::Half => 1,
-                GraphemeWidth::Full => 2,
-            })
-            .sum()
+        self.boundary_at_grapheme(grapheme_idx).col_idx
     }
 
     pub fn width(&self) -> ColIdx {
-        self.width_until(self.grapheme_count())
+        self.end().col_idx
     } //convenience method to simplify CommandBar implementation
-      // Inserts a character into the line, or appends it at the end if at == grapheme_count + 1
 
-    pub fn insert_char(&mut self, character: char, at: GraphemeIdx) {
-        debug_assert!(at.saturating_sub(1) <= self.grapheme_count());
-        if let Some(fragment) = self.fragments.get(at) {
-            self.string.insert(fragment.start, character);
-            // use convenience method provided by string to insert character at byte_idx
-        } else {
-            self.string.push(character);
-        } //if no fragment found, character should be added at end
-        self.rebuild_fragments(); // rebuild fragments to acccount for updated clusters and byte indices
+    // Replaces the given bytes by text. Only the chunks around the edit are segmented again, the boundaries behind them are shifted.
+    fn replace_range(&mut self, range: Range<ByteIdx>, text: &str) {
+        debug_assert!(!text.contains('\n'));
+        self.string.replace_range(range.clone(), text);
+        *self.wrapped.get_mut() = None;
+        let Some(boundaries) = self.boundaries.take() else {
+            return; // not indexed yet, nothing to update
+        };
+        let shift = |boundary: &Boundary| {
+            boundary
+                .byte_idx
+                .saturating_sub(range.len())
+                .saturating_add(text.len())
+        };
+        // the edit may merge graphemes with the text around it, so re-indexing starts at the last boundary in front of it
+        // and ends at the first boundary behind it which is still a grapheme boundary. The end of the line always is one.
+        let first = boundaries
+            .partition_point(|boundary| boundary.byte_idx < range.start)
+            .saturating_sub(1);
+        let last = boundaries
+            .iter()
+            .enumerate()
+            .skip(first.saturating_add(1))
+            .position(|(idx, boundary)| {
+                boundary.byte_idx >= range.end
+                    && (idx.saturating_add(1) == boundaries.len() || self.is_grapheme_boundary(shift(boundary)))
+            })
+            .map_or(boundaries.len().saturating_sub(1), |offset| {
+                first.saturating_add(1).saturating_add(offset)
+            });
+        let (Some(&from), Some(&old_to)) = (boundaries.get(first), boundaries.get(last)) else {
+            return;
+        };
+        let (reindexed, to) = self.index(from, shift(&old_to));
+        let mut updated: Vec<Boundary> = boundaries.iter().take(first).copied().collect();
+        updated.extend(reindexed);
+        updated.extend(boundaries.iter().skip(last).map(|boundary| Boundary {
+            byte_idx: shift(boundary),
+            grapheme_idx: boundary
+                .grapheme_idx
+                .saturating_sub(old_to.grapheme_idx)
+                .saturating_add(to.grapheme_idx),
+            col_idx: boundary
+                .col_idx
+                .saturating_sub(old_to.col_idx)
+                .saturating_add(to.col_idx),
+        }));
+        self.boundaries = OnceCell::from(updated);
+    }
+
+    fn is_grapheme_boundary(&self, byte_idx: ByteIdx) -> bool {
+        GraphemeCursor::new(byte_idx, self.string.len(), true)
+            .is_boundary(&self.string, 0)
+            .unwrap_or(false)
+    }
+
+    // Inserts text, which must not contain a line break, at the given byte index
+    pub fn insert_str(&mut self, at: ByteIdx, text: &str) {
+        self.replace_range(at..at, text);
+    }
+
+    pub fn remove(&mut self, range: Range<ByteIdx>) {
+        self.replace_range(range, "");
     }
 
     pub fn append_char(&mut self, character: char) {
-        self.insert_char(character, self.grapheme_count());
+        self.insert_str(self.string.len(), character.encode_utf8(&mut [0; 4]));
     }
 
     pub fn delete(&mut self, at: GraphemeIdx) {
         debug_assert!(at <= self.grapheme_count());
-        if let Some(fragment) = self.fragments.get(at) {
-            let start = fragment.start;
-            let end = fragment
-                .start
-                .saturating_add(fragment.grapheme.len());
-            self.string.drain(start..end); //removes substring from start to end
-            self.rebuild_fragments();
+        if at < self.grapheme_count() {
+            let start = self.grapheme_idx_to_byte_idx(at);
+            let end = self.grapheme_idx_to_byte_idx(at.saturating_add(1));
+            self.remove(start..end);
         }
     }
 
@@ -220,131 +392,125 @@ impl Line {
         self.delete(self.grapheme_count().saturating_sub(1));
     }
 
-    pub fn append(&mut self, other: &Self) {
-        self.string.push_str(&other.string);
-        self.rebuild_fragments(); // update existing string and remove all fragments and rebuild
-    }
-
     pub fn split(&mut self, at: GraphemeIdx) -> Self {
-        if let Some(fragment) = self.fragments.get(at) {
-            let remainder = self.string.split_off(fragment.start);
-            self.rebuild_fragments();
-            Self::from(&remainder)
+        if at < self.grapheme_count() {
+            let start = self.grapheme_idx_to_byte_idx(at);
+            let remainder = Self::from(self.string.get(start..).unwrap_or_default());
+            self.remove(start..self.string.len());
+            remainder
         } else {
             Self::default()
         }
     }
 
+    // Returns the grapheme starting at byte_idx, or None if byte_idx is not on a grapheme boundary.
     fn byte_idx_to_grapheme_idx(&self, byte_idx: ByteIdx) -> Option<GraphemeIdx> {
-       if byte_idx > self.string.len() {
-            return None;
+        if byte_idx == self.string.len() {
+            return Some(self.grapheme_count());
         }
-        self.fragments
-            .iter()
-            .position(|fragment| fragment.start >= byte_idx)
+        self.fragments_from(self.chunk_start(|boundary| boundary.byte_idx, byte_idx))
+            .map(|(boundary, _)| boundary)
+            .take_while(|boundary| boundary.byte_idx <= byte_idx)
+            .find(|boundary| boundary.byte_idx == byte_idx)
+            .map(|boundary| boundary.grapheme_idx)
     }
 
-    fn grapheme_idx_to_byte_idx(&self, grapheme_idx: GraphemeIdx) -> ByteIdx {
+    pub fn grapheme_idx_to_byte_idx(&self, grapheme_idx: GraphemeIdx) -> ByteIdx {
         debug_assert!(grapheme_idx <= self.grapheme_count());
-        if grapheme_idx == 0 || self.grapheme_count() == 0 {
-            return 0;
-        }
-        self.fragments.get(grapheme_idx).map_or_else(
-            || {
-                #[cfg(debug_assertions)]
-                {
-                    panic!("Fragment not found for grapheme index: {grapheme_idx:?}");
-                }
-                #[cfg(not(debug_assertions))]
-                {
-                    0
-                }
-            },
-            |fragment| fragment.start,
-        )
+        self.boundary_at_grapheme(grapheme_idx).byte_idx
     }
 
+    // Returns the graphemes covered by the first match at or after from_grapheme_idx.
     pub fn search_forward(
         &self,
-        query: &str,
+        query: &SearchQuery,
         from_grapheme_idx: GraphemeIdx,
-    ) -> Option<GraphemeIdx> {
+    ) -> Option<Range<GraphemeIdx>> {
         debug_assert!(from_grapheme_idx <= self.grapheme_count());
         if from_grapheme_idx == self.grapheme_count() {
             return None;
         }
         let start = self.grapheme_idx_to_byte_idx(from_grapheme_idx);
-        self.find_all(query, start..self.string.len())
-            .first()
-            .map(|(_, grapheme_idx)| *grapheme_idx)
+        self.find_matches(query, start..self.string.len())
+            .next()
+            .and_then(|found| self.byte_range_to_grapheme_range(found))
     }
 
+    // Returns the graphemes covered by the last match ending before from_grapheme_idx.
+    // Windows growing backwards from there are searched, so only the end of a long line is scanned if the match is close.
     pub fn search_backward(
         &self,
-        query: &str,
+        query: &SearchQuery,
         from_grapheme_idx: GraphemeIdx,
-    ) -> Option<GraphemeIdx> {
+    ) -> Option<Range<GraphemeIdx>> {
         
         debug_assert!(from_grapheme_idx <= self.grapheme_count());
 
         if from_grapheme_idx == 0 {
             return None;
         }
-        let end_byte_index = if from_grapheme_idx == self.grapheme_count() {
-            self.string.len()
-        } else {
-            self.grapheme_idx_to_byte_idx(from_grapheme_idx)
-        }; //create a substring to search in. for backward search , go from beginning to current loc
-        self.find_all(query, 0..end_byte_index)
-            .last()
-            .map(|(_, grapheme_idx)| *grapheme_idx)
+        let end_byte_index = self.grapheme_idx_to_byte_idx(from_grapheme_idx);
+        let mut window_len = CHUNK_LEN;
+        loop {
+            let window_start = self.string.floor_char_boundary(end_byte_index.saturating_sub(window_len));
+            if let Some(found) = self.find_matches(query, window_start..end_byte_index).last() {
+                return self.byte_range_to_grapheme_range(found);
+            }
+            if window_start == 0 {
+                return None;
+            }
+            window_len = window_len.saturating_mul(2);
+        }
     }
 
-    pub fn find_all(&self, query: &str, range: Range<ByteIdx>)->Vec<(ByteIdx,GraphemeIdx)>{
+    // Lazily finds the non-empty matches which lie completely within range and start and end on grapheme boundaries, left to right.
+    // A match reaching beyond the range is replaced by the one found in the text cut off at the range end, e.g. `foo.*` then ends there.
+    pub fn find_matches<'a>(
+        &'a self,
+        query: &'a SearchQuery,
+        range: Range<ByteIdx>,
+    ) -> impl Iterator<Item = Range<ByteIdx>> + 'a {
         let end = min(range.end, self.string.len());
-        let start = range.start;
+        let mut start = range.start;
         debug_assert!(start<=end);
-        debug_assert!(start<=self.string.len());
-        self.string.get(start..end).map_or_else(Vec::new, |substr|{
-            let potential_matches: Vec<ByteIdx> = substr
-                .match_indices(query)//find potential matches within substr
-                .map(|(relative_start_idx,_)|{
-                    relative_start_idx.saturating_add(start)//convert rel to abs
-                })
-                .collect();
-                //we collected vector of potential matches which need double check
-
-            self.match_grapheme_clusters(&potential_matches, query)//convert potential matches to matches aligning with grapheme boundaries
-            //maps potential vectors to desired pair of start byte idx / grapheme idx
+        iter::from_fn(move || {
+            while let Some(mut found) = query.find_at(&self.string, start) {
+                if found.start >= end {
+                    return None; //all further matches lie behind the searched range
+                }
+                if found.end > end {
+                    //nothing starts before this match, so without a match in the cut off text there is none left in the range
+                    found = query.find_at(&self.string[..end], found.start)?;
+                }
+                start = if found.is_empty() {
+                    //empty matches (e.g. of `a*`) are skipped, continue behind the next char
+                    let character = self.string.get(found.start..).and_then(|rest| rest.chars().next())?;
+                    found.start.saturating_add(character.len_utf8())
+                } else {
+                    found.end
+                };
+                //a match is only genuine if it starts and ends on grapheme boundaries
+                if !found.is_empty() && self.is_grapheme_boundary(found.start) && self.is_grapheme_boundary(found.end) {
+                    return Some(found);
+                }
+            }
+            None
         })
     }
 
-    fn match_grapheme_clusters( 
-        &self,
-        matches: &[ByteIdx],
-        query: &str,
-    )->Vec<(ByteIdx,GraphemeIdx)>{
-        let grapheme_count = query.graphemes(true).count();
-        //need graphemes within query to check for genuine match 
-        matches
-            .iter()
-            .filter_map(|&start|{ // iterate over all matches(start is start index of potential match) where filter map removes elements by returning None and matching others to new structre by returning Some()
-                self.byte_idx_to_grapheme_idx(start)//get grapheme idx if present else not a genuine match
-                .and_then(|grapheme_idx|{
-                    self.fragments
-                    .get(grapheme_idx..grapheme_idx.saturating_add(grapheme_count))//get all fragments to be part of match
-                    .and_then(|fragments|{
-                        let substring = fragments
-                            .iter()
-                            .map(|fragment|fragment.grapheme.as_str())
-                            .collect::<String>();//combine fragments to single string
-                        (substring==query).then_some((start,grapheme_idx))
-                        //if combined string matches query we have actual answer
-                    })
-                })
+    fn byte_range_to_grapheme_range(&self, range: Range<ByteIdx>) -> Option<Range<GraphemeIdx>> {
+        Some(self.byte_idx_to_grapheme_idx(range.start)?..self.byte_idx_to_grapheme_idx(range.end)?)
+    }
 
-            })
-            .collect()
+    // The bytes of the graphemes which are at least partly displayed within the given columns
+    pub fn byte_range_of_columns(&self, columns: Range<ColIdx>) -> Range<ByteIdx> {
+        let start = self.boundary_at_col(columns.start).byte_idx;
+        let last = self.boundary_at_col(columns.end.saturating_sub(1));
+        let end = self
+            .fragments_from(last)
+            .next()
+            .map_or(last.byte_idx, |(_, fragment)| fragment.end());
+        start..end.max(start)
     }
 }
 
@@ -360,3 +526,8 @@ impl Deref for Line {
         &self.string
     }
 }
+
+// Word boundaries also delimit whitespace and punctuation, only segments containing letters or digits count as words
+fn is_word(segment: &str) -> bool {
+    segment.chars().any(char::is_alphanumeric)
+}
@@ -1,9 +1,15 @@
 use crate::prelude::*;
 use super::GraphemeWidth;
-#[derive(Clone,Debug)]
-pub struct TextFragment {
-    pub grapheme: String,
+#[derive(Clone, Copy, Debug)]
+pub struct TextFragment<'a> {
+    pub grapheme: &'a str, // borrowed from the line, fragments are computed on demand
     pub rendered_width: GraphemeWidth,
     pub replacement: Option<char>,
     pub start: ByteIdx,// keep track of start byte index for grapheme
-}
\ No newline at end of file
+}
+
+impl TextFragment<'_> {
+    pub const fn end(&self) -> ByteIdx {
+        self.start.saturating_add(self.grapheme.len())
+    }
+}
@@ -0,0 +1,46 @@
+use std::fmt::{Display, Formatter, Result};
+
+#[derive(Default, Eq, PartialEq, Debug, Copy, Clone)]
+pub enum LineEnding {
+    #[default]
+    Lf,
+    CrLf,
+    Mixed, // lines are split at '\n' and keep their '\r', so they are written back unchanged
+}
+
+impl LineEnding {
+    pub fn detect(contents: &str) -> Self {
+        let crlf_count = contents.matches("\r\n").count();
+        let lf_count = contents.matches('\n').count().saturating_sub(crlf_count);
+        match (crlf_count, lf_count) {
+            (0, _) => Self::Lf,
+            (_, 0) => Self::CrLf,
+            _ => Self::Mixed,
+        }
+    }
+
+    pub const fn as_str(self) -> &'static str {
+        match self {
+            Self::Lf | Self::Mixed => "\n",
+            Self::CrLf => "\r\n",
+        }
+    }
+
+    // The ending to convert to: LF and CRLF are swapped, mixed files are normalized to LF
+    pub const fn converted(self) -> Self {
+        match self {
+            Self::Lf => Self::CrLf,
+            Self::CrLf | Self::Mixed => Self::Lf,
+        }
+    }
+}
+
+impl Display for LineEnding {
+    fn fmt(&self, formatter: &mut Formatter<'_>) -> Result {
+        match self {
+            Self::Lf => write!(formatter, "LF"),
+            Self::CrLf => write!(formatter, "CRLF"),
+            Self::Mixed => write!(formatter, "Mixed"),
+        }
+    }
+}
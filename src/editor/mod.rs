@@ -1,46 +1,82 @@
 use crate::prelude::*;
-use crossterm::event::{read, Event, KeyEvent, KeyEventKind};
+use crossterm::event::{poll, read, Event, KeyEvent, KeyEventKind};
 use std::{
+    cmp::{min, Reverse},
     env,
+    fs::canonicalize,
+    iter::once,
+    mem::{replace, take},
+    path::{Path, PathBuf},
     io::Error,
     panic::{set_hook, take_hook},
+    thread::panicking,
+    time::Duration,
 };
+use unicode_width::UnicodeWidthStr;
 
 mod annotatedstring;
 pub mod annotationtype;
+mod clipboard;
 mod command;
+mod config;
 mod uicomponents;
 mod documentstatus;
 mod line;
 mod terminal;
+mod filefinder;
 mod filetype;
+mod fuzzymatch;
+mod lineending;
+mod pathcompletion;
+mod scheduler;
+mod searchoptions;
+mod searchquery;
 pub use annotationtype::AnnotationType;
 mod annotation;
 use annotation::Annotation;
+use clipboard::Clipboard;
+use config::Config;
+use filefinder::FileFinder;
 use filetype::FileType;
+use fuzzymatch::fuzzy_score;
+use lineending::LineEnding;
+use pathcompletion::{expand_home, PathCompletion};
+use scheduler::{Scheduler, Timer};
+use searchoptions::{SearchOption, SearchOptions};
+use searchquery::SearchQuery;
 use annotatedstring::AnnotatedString;
-use uicomponents::{CommandBar, MessageBar, View, StatusBar, UIComponent};//contains the components as a whole
+use uicomponents::{CandidateList, CommandBar, Layout, MessageBar, Pane, SplitDirection, View, StatusBar, UIComponent};//contains the components as a whole
 use documentstatus::DocumentStatus;
 use line::Line;
 
 use terminal::Terminal;
 
 use self::command::{
-    Command::{self, Edit, Move, System},
-    Edit::InsertNewLine,
+    Command::{self, Edit, Move, Select, System},
+    Edit::{Copy, Cut, Insert, InsertNewLine, InsertText, Paste},
     Move::{Up,Down,Left,Right},
-    System::{Dismiss, Quit, Resize, Save, Search},
+    System::{Dismiss, GotoLine, Quit, Redraw, Replace, Resize, Save, Search, ToggleLineNumbers, ToggleSearchOption, ToggleSoftWrap, ConvertLineEndings, Open, SwitchBuffer, NextBuffer, PreviousBuffer, SplitPane, FocusNextPane, ClosePane, GrowPane, ShrinkPane, FindFile},
 
 
 
 };
 
-const QUIT_TIMES: u8 = 3; // hardcoding amt of times to press Ctrl+Q
+const DISK_CHECK_INTERVAL: Duration = Duration::from_secs(1); // time between two checks of the file on disk
+const FILE_LIST_ROWS: usize = 10; // candidates shown by the file finder
 
 #[derive(Eq, PartialEq, Default)]
 enum PromptType{
     Search,
     Save,
+    Replace,//asks for the query
+    ReplaceWith,//asks for the replacement
+    ReplaceConfirm,//asks what to do with the current match
+    GotoLine,
+    FileChanged,//asks what to do about changes made to the file by another process
+    Recover,//asks whether to recover unsaved changes from a crashed session
+    Open,//asks for a file to open in a new buffer, Tab completes the path
+    SwitchBuffer,//fuzzy filters the open buffers
+    FindFile,//fuzzy filters the files below the working directory
     #[default]//derive default trait for only None variant of the enum PromptType
     None,
 }
@@ -54,14 +90,24 @@ impl PromptType{
 #[derive(Default)]
 pub struct Editor {
     should_quit: bool,
-    view: View,
-    status_bar: StatusBar,
+    view: View,//the active buffer, shown in the focused pane
+    buffers: Vec<View>,//the other open buffers, in the order they follow the active one
+    buffer_matches: Vec<usize>,//buffers matching the switcher prompt, best first, see buffer_names for the indices
+    status_bar: StatusBar,//status line of the focused pane
+    layout: Layout,//the other panes
+    pane_origin: Position,
+    pane_size: Size,//area of the focused pane, including its status line
     title: String,
     message_bar: MessageBar,
     command_bar: CommandBar,
     prompt_type: PromptType,//used to steer in prompt type we are on
+    path_completion: Option<PathCompletion>,//candidates Tab cycles through in the open and save prompts
+    file_finder: FileFinder,//only filled while the file finder is open
+    file_list: CandidateList,
     terminal_size: Size,
     quit_times: u8,
+    clipboard: Clipboard,
+    scheduler: Scheduler,
 }
 
 impl Editor {
@@ -72,25 +118,55 @@ impl Editor {
             let _ = Terminal::terminate();
             current_hook(panic_info);
         }));
+        let args: Vec<String> = env::args().skip(1).collect();
+        //settings are read before anything is drawn, project files are looked for starting at the first file
+        let (config, config_errors) = Config::load(&Self::project_dir(args.first().map(String::as_str)));
+        config.install();
+
         Terminal::initialize()?; // Setup terminal (alternate screen, raw mode)
 
         let mut editor = Self::default();
         let size = Terminal::size().unwrap_or_default();
         editor.handle_resize_command(size); // using default struct and calling resize on it to set up properly
+        editor.scheduler.schedule(Timer::DiskCheck, DISK_CHECK_INTERVAL);
 
         editor.update_message("HELP: Ctrl+F = Find | Ctrl+S = Save | Ctrl+Q = Quit");
+        if let Some(first_error) = config_errors.first() {
+            let more = match config_errors.len().saturating_sub(1) {
+                0 => String::new(),
+                count => format!(" (and {count} more)"),
+            };
+            editor.update_message(&format!("Config error: {first_error}{more}"));
+        }
 
-        let args: Vec<String> = env::args().collect();
-        if let Some(file_name) = args.get(1) {
+        for file_name in &args {
             debug_assert!(!file_name.is_empty());
-            if editor.view.load(file_name).is_err() {
-                 editor.update_message(&format!("ERR: Could not open file: {file_name}"));
-            } // Load file into buffer if filename given
+            match editor.load_view(file_name) {
+                // the first file becomes the active buffer, the others follow in order
+                Ok(view) if !editor.view.is_file_loaded() => editor.view = view,
+                Ok(view) => {
+                    editor.buffers.push(view);
+                    editor.check_project_config(file_name);
+                }
+                Err(_) => editor.update_message(&format!("ERR: Could not open file: {file_name}")),
+            }
+        }
+
+        if editor.view.has_newer_swap_file() {
+            editor.set_prompt(PromptType::Recover);
         }
 
         editor.refresh_status(); // ask to refresh status, this method is called in every rendering cycle too
         Ok(editor)
     }
+    // The directory of file_name, or the working directory if no file was given
+    fn project_dir(file_name: Option<&str>) -> PathBuf {
+        let dir = file_name
+            .and_then(|file_name| Path::new(&expand_home(file_name)).parent().map(Path::to_path_buf))
+            .filter(|dir| !dir.as_os_str().is_empty())
+            .unwrap_or_else(|| PathBuf::from("."));
+        canonicalize(&dir).unwrap_or(dir)
+    }
     // end region
     //region : Event Loop
     pub fn run(&mut self) {
@@ -100,12 +176,18 @@ impl Editor {
             if self.should_quit {
                 break;
             }
-            match read() {
-                Ok(event) => self.evaluate_event(event), // listen to keyboard or screen resize events
-                error => {
+            //wait for input, but no longer than until the next timer fires
+            let event = match self.scheduler.timeout() {
+                Some(timeout) => poll(timeout).and_then(|ready| if ready { read().map(Some) } else { Ok(None) }),
+                None => read().map(Some),
+            };
+            match event {
+                Ok(Some(event)) => self.evaluate_event(event), // listen to keyboard or screen resize events
+                Ok(None) => {}
+                Err(err) => {
                     #[cfg(debug_assertions)]
                     {
-                        panic!("Could not read event: {error:?}");
+                        panic!("Could not read event: {err:?}");
                     }
                        #[cfg(not(debug_assertions))]
                     {
@@ -113,9 +195,24 @@ impl Editor {
                     }
                 }
             }
+            for timer in self.scheduler.take_due() {
+                self.handle_timer(timer);
+            }
+            self.update_swap_file();
             self.refresh_status();//we have better method to refresh now
         }
     }
+    fn handle_timer(&mut self, timer: Timer) {
+        match timer {
+            Timer::MessageExpiry => self.message_bar.set_needs_redraw(true),
+            Timer::SwapFile => {} // written below, along with every other pending swap file update
+            Timer::DiskCheck => {
+                self.check_disk_changes();
+                self.scheduler.schedule(Timer::DiskCheck, DISK_CHECK_INTERVAL);
+            }
+            Timer::FileWalk => self.walk_files(),
+        }
+    }
     
         fn refresh_screen(&mut self) {
 
@@ -126,21 +223,38 @@ impl Editor {
         let bottom_bar_row = self.terminal_size.height.saturating_sub(1);
         let _ = Terminal::hide_caret();
         //start adding ui elements from bottom
-
+        let bottom_bar_origin = Position{
+            row: bottom_bar_row,
+            col: 0,
+        };
         if self.in_prompt(){
-            self.command_bar.render(bottom_bar_row);
+            self.command_bar.render(bottom_bar_origin);
         }else{
-            self.message_bar.render(bottom_bar_row);
+            self.message_bar.render(bottom_bar_origin);
         }
 
-
-        if self.terminal_size.height > 1 {
-            self.status_bar
-                .render(self.terminal_size.height.saturating_sub(2));
-        } //if height is atleast 2 , render status bar too
-        if self.terminal_size.height > 2 {
-            self.view.render(0);
-        } //if height is greater than 2 , render view
+        let view_needs_redraw = self.view.needs_redraw();
+        if view_needs_redraw{
+            self.layout.redraw_panes_showing(&self.view);//changes to the buffer show up in every pane showing it
+            self.file_list.set_needs_redraw(true);//the file list is drawn on top of the view
+        }
+        self.layout.render();
+        let status_row = self.pane_origin.row.saturating_add(self.pane_size.height).saturating_sub(1);
+        if self.pane_size.height > 0 {
+            self.status_bar.render(Position{
+                row: status_row,
+                col: self.pane_origin.col,
+            });
+        } //the status line takes the last row of the pane
+        if self.pane_size.height > 1 {
+            self.view.render(self.pane_origin);
+        } //if there is room left, render view above it
+        if self.prompt_type == PromptType::FindFile{
+            self.file_list.render(Position{
+                row: status_row.saturating_sub(self.file_list.height()),
+                col: self.pane_origin.col,
+            });
+        }
 
         let new_caret_pos = if self.in_prompt(){
             Position{
@@ -148,13 +262,14 @@ impl Editor {
                 col: self.command_bar.caret_position_col(),
             }
         }else{
-            self.view.caret_position()
+            self.view.caret_position().saturating_add(self.pane_origin)
         };//ensure that caret block is correctly placed , if no command bar present do the same as before by querying View
         // if command bar present calc correct pos based on column within command_bar and its posn on terminal
         debug_assert!(new_caret_pos.col <= self.terminal_size.width);
         debug_assert!(new_caret_pos.row <= self.terminal_size.height);
 
 
+        let _ = Terminal::present(); // prints the rows which changed since the last frame
         let _ = Terminal::move_caret_to(new_caret_pos);
 
         let _ = Terminal::show_caret();
@@ -165,6 +280,7 @@ impl Editor {
         let status = self.view.get_status();
         let title = format!("{} - {NAME}", status.file_name);
         self.status_bar.update_status(status);
+        self.layout.refresh_status();
 
         if title != self.title && matches!(Terminal::set_title(&title), Ok(())) {
             self.title = title;
@@ -174,13 +290,11 @@ impl Editor {
     fn evaluate_event(&mut self, event: Event) {
         let should_process = match &event {
             Event::Key(KeyEvent { kind, .. }) => kind == &KeyEventKind::Press,
-            Event::Resize(_, _) => true,
+            Event::Resize(_, _) | Event::Paste(_) => true,
             _ => false,
         };
-        if should_process {
-            if let Ok(command) = Command::try_from(event) {
-                self.process_command(command); // logic to work on a command is handled in othe fxn
-            }
+        if should_process && let Ok(command) = Command::try_from(event) {
+            self.process_command(command); // logic to work on a command is handled in othe fxn
         }
     }
     //end region
@@ -190,12 +304,25 @@ impl Editor {
         if let System(Resize(size))= command{
             self.handle_resize_command(size);
             return;
+        }
+        if let System(Redraw) = command{
+            let _ = Terminal::force_repaint();
+            return;
         }
          // this block is there to correctly handle multiple quit events, or reset quit times, also resizing terminal shouldnt reset quit counter
     
         match self.prompt_type{
             PromptType::Search => self.process_command_during_search(command),
             PromptType::Save => self.process_command_during_save(command),
+            PromptType::Replace => self.process_command_during_replace(command),
+            PromptType::ReplaceWith => self.process_command_during_replace_with(command),
+            PromptType::ReplaceConfirm => self.process_command_during_replace_confirm(&command),
+            PromptType::GotoLine => self.process_command_during_goto_line(command),
+            PromptType::FileChanged => self.process_command_during_file_changed(&command),
+            PromptType::Recover => self.process_command_during_recover(&command),
+            PromptType::Open => self.process_command_during_open(command),
+            PromptType::SwitchBuffer => self.process_command_during_switch_buffer(command),
+            PromptType::FindFile => self.process_command_during_find_file(command),
             PromptType::None => self.process_command_no_prompt(command),
         }
     }
@@ -207,40 +334,106 @@ impl Editor {
         }
         self.reset_quit_times();//reset quit times for all other commands
         match command{
-            System(Quit | Resize(_) | Dismiss)=>{}, // handled above
+            System(Quit | Resize(_) | Redraw | Dismiss | ToggleSearchOption(_))=>{}, // handled above or not applicable
             System(Search)=>self.set_prompt(PromptType::Search),
+            System(Replace)=>self.set_prompt(PromptType::Replace),
+            System(GotoLine)=>self.set_prompt(PromptType::GotoLine),
+            System(Open)=>self.set_prompt(PromptType::Open),
+            System(SwitchBuffer)=>self.set_prompt(PromptType::SwitchBuffer),
+            System(FindFile)=>self.set_prompt(PromptType::FindFile),
+            System(NextBuffer)=>self.activate_buffer(min(1, self.buffers.len())),
+            System(PreviousBuffer)=>self.activate_buffer(self.buffers.len()),
+            System(SplitPane(direction))=>self.split_pane(direction),
+            System(FocusNextPane)=>self.focus_next_pane(),
+            System(ClosePane)=>self.close_pane(),
+            System(GrowPane)=>self.resize_pane(true),
+            System(ShrinkPane)=>self.resize_pane(false),
+            System(ToggleLineNumbers)=>{
+                let line_numbers = self.view.cycle_line_numbers();
+                self.update_message(&format!("Line numbers: {line_numbers}"));
+            }
+            System(ConvertLineEndings)=>{
+                let line_ending = self.view.convert_line_endings();
+                self.update_message(&format!("Line endings converted to {line_ending}"));
+            }
+            System(ToggleSoftWrap)=>{
+                let soft_wrap = if self.view.toggle_soft_wrap() { "on" } else { "off" };
+                self.update_message(&format!("Soft wrap: {soft_wrap}"));
+            }
             System(Save)=>self.handle_save_command(),
+            Edit(Cut)=>self.handle_cut_command(),
+            Edit(Copy)=>self.handle_copy_command(),
+            Edit(Paste)=>self.handle_paste_command(),
             Edit(edit_command)=>self.view.handle_edit_command(edit_command),
             Move(move_command)=>self.view.handle_move_command(move_command),
+            Select(move_command)=>self.view.handle_select_command(move_command),
         }
     }
     //end region
     //region: Resize Command Handling
         fn handle_resize_command(&mut self, size: Size) {
         self.terminal_size = size;
-        self.view.resize(Size {
-            height: size.height.saturating_sub(2),
-            width: size.width,
-        });
+        let _ = Terminal::resize(size);
+        self.arrange_panes();
         let bar_size=Size{
             height:1,
             width: size.width,
         };
         self.message_bar.resize(bar_size);
-        self.status_bar.resize(bar_size);
         self.command_bar.resize(bar_size);
     } // defining the sizes and height of the ui
+    // Lays out the panes above the message bar and resizes the focused pane and the background buffers to fit
+    fn arrange_panes(&mut self) {
+        let (origin, size) = self.layout.arrange(Size {
+            height: self.terminal_size.height.saturating_sub(1),
+            width: self.terminal_size.width,
+        });
+        self.pane_origin = origin;
+        self.pane_size = size;
+        let view_size = self.view_size();
+        self.view.resize(view_size);
+        for view in &mut self.buffers {
+            view.resize(view_size);
+        }
+        self.status_bar.resize(Size{
+            height: 1,
+            width: size.width,
+        });
+        self.file_list.resize(Size{
+            height: min(FILE_LIST_ROWS, view_size.height),
+            width: view_size.width,
+        });
+    }
+    fn view_size(&self) -> Size {
+        Size {
+            height: self.pane_size.height.saturating_sub(1),
+            width: self.pane_size.width,
+        }
+    }
     //end region
     //region: Quit Command Handling
     #[allow(clippy::arithmetic_side_effects)]
     fn handle_quit_command(&mut self){
-        if !self.view.get_status().is_modified || self.quit_times + 1 == QUIT_TIMES{
+        let views: Vec<&View> = self.all_views().collect();
+        //a buffer shown in several panes is listed once
+        let modified_files: Vec<String> = views
+            .iter()
+            .enumerate()
+            .filter(|&(idx, view)| {
+                view.get_status().is_modified
+                    && !views[..idx].iter().any(|other| other.shows_same_buffer(view))
+            })
+            .map(|(_, view)| view.get_status().file_name)
+            .collect();
+        let quit_times = Config::get().quit_times;
+        if modified_files.is_empty() || self.quit_times.saturating_add(1) >= quit_times{
             self.should_quit=true;
-        }else if self.view.get_status().is_modified{
-            //handle the case where view is modified and user wants to do more work 
+        }else{
+            //handle the case where any buffer is modified and user wants to do more work 
             self.update_message(&format!(
-                "WARNING! File has unsaved changes. Press Ctrl+Q {} more times to quit. ",
-                QUIT_TIMES - self.quit_times -1 
+                "WARNING! Unsaved changes in {}. Press Ctrl+Q {} more times to quit. ",
+                modified_files.join(", "),
+                quit_times.saturating_sub(self.quit_times).saturating_sub(1)
             ));
             self.quit_times += 1;
         }
@@ -255,9 +448,29 @@ impl Editor {
     }
     //end region
 
+    //region: Clipboard Command Handling
+    fn handle_cut_command(&mut self){
+        if let Some(text) = self.view.cut_selection(){
+            self.clipboard.set(&text);
+        }
+    }
+    fn handle_copy_command(&mut self){
+        if let Some(text) = self.view.copy_selection(){
+            self.clipboard.set(&text);
+        }
+    }
+    fn handle_paste_command(&mut self){
+        if let Some(text) = self.clipboard.get(){
+            self.view.handle_edit_command(InsertText(text));//pasting behaves like typing the whole text at once
+        }
+    }
+    //end region
+
     //region: Save Command Handling
         fn handle_save_command(&mut self){
-        if self.view.is_file_loaded() {
+        if self.view.is_changed_on_disk() {
+            self.set_prompt(PromptType::FileChanged); //don't clobber changes made by another process without asking
+        } else if self.view.is_file_loaded() {
             self.save(None);
         } else {
             self.set_prompt(PromptType::Save);
@@ -265,17 +478,21 @@ impl Editor {
     }//calls save  or opens a prompt depending on status of file being loaded or not
     fn process_command_during_save(&mut self, command: Command){
         match command{
-             System(Quit | Resize(_) | Search | Save) | Move(_) => {} // Not applicable during save, Resize already handled at this stage
+             System(Quit | Resize(_) | Redraw | Search | Replace | GotoLine | Save | ToggleSearchOption(_) | ToggleLineNumbers | ToggleSoftWrap | ConvertLineEndings | Open | SwitchBuffer | NextBuffer | PreviousBuffer | SplitPane(_) | FocusNextPane | ClosePane | GrowPane | ShrinkPane | FindFile) | Move(_) | Select(_) => {} // Not applicable during save, Resize already handled at this stage
              System(Dismiss)=>{
                 self.set_prompt(PromptType::None);
                 self.update_message("Save Aborted.");
              }
              Edit(InsertNewLine)=>{
-                let file_name = self.command_bar.value();
+                let file_name = expand_home(&self.command_bar.value());
                 self.save(Some(&file_name));
                 self.set_prompt(PromptType::None);
              }
-             Edit(edit_command)=> self.command_bar.handle_edit_command(edit_command),
+             Edit(Insert('\t'))=>self.complete_path(),
+             Edit(edit_command)=>{
+                self.command_bar.handle_edit_command(edit_command);
+                self.reset_path_completion();
+             }
         }
     }
     fn save(&mut self, file_name: Option<&str>){
@@ -284,12 +501,9 @@ impl Editor {
         } else {
             self.view.save()
         };
-        if result.is_ok() {
-            if self.view.save().is_ok(){
-                self.update_message("File saved successfully.");
-            } else{
-                self.update_message("Error writing file!");
-            }
+        match result {
+            Ok(()) => self.update_message("File saved successfully."),
+            Err(err) => self.update_message(&format!("Error writing file: {err}")),
         }
     }
     //end region
@@ -308,19 +522,520 @@ impl Editor {
             }
             Edit(edit_command)=>{
                 self.command_bar.handle_edit_command(edit_command);
-                let query = self.command_bar.value();
-                self.view.search(&query);//handle input and perform actual search
+                self.search_for_prompt_value("Search");//handle input and perform actual search
+            }
+            System(ToggleSearchOption(option))=>{
+                self.view.toggle_search_option(option);
+                self.search_for_prompt_value("Search");
             }
             Move(Right | Down)=> self.view.search_next(),
             Move(Up | Left) => self.view.search_prev(),
-            System(Quit| Resize(_)| Search | Save)| Move(_)=>{}
+            System(Quit| Resize(_) | Redraw | Search | Replace | GotoLine | Save | ToggleLineNumbers | ToggleSoftWrap | ConvertLineEndings | Open | SwitchBuffer | NextBuffer | PreviousBuffer | SplitPane(_) | FocusNextPane | ClosePane | GrowPane | ShrinkPane | FindFile)| Move(_) | Select(_)=>{}
         }
     } 
+
+    // Searches for the value entered in the prompt. An invalid pattern is reported in the prompt, previous results stay visible.
+    fn search_for_prompt_value(&mut self, action: &str){
+        let query = self.command_bar.value();
+        let is_valid_query = self.view.search(&query).is_ok();
+        let prompt = self.search_prompt(action, is_valid_query);
+        self.command_bar.set_prompt(&prompt);
+    }
+
+    fn search_prompt(&self, action: &str, is_valid_query: bool)->String{
+        let options = self.view.get_search_options();//active options are listed right behind the action
+        if is_valid_query{
+            format!("{action}{options} (Esc to cancel, Arrows to navigate, Alt+R/C/W for regex/case/word) : ")
+        }else{
+            format!("{action}{options} (invalid pattern) : ")
+        }
+    }
     //end region 
 
+    //region: Replace Command & Prompt Handling
+    fn process_command_during_replace(&mut self, command: Command){
+        //entering the query works like searching, the matches are highlighted while typing
+        match command{
+            System(Dismiss) =>{
+                self.set_prompt(PromptType::None);
+                self.view.dismiss_search();
+                self.update_message("Replace aborted.");
+            }
+            Edit(InsertNewLine)=>{
+                if self.command_bar.value().is_empty(){
+                    self.set_prompt(PromptType::None);
+                    self.view.dismiss_search();
+                }else{
+                    self.set_prompt(PromptType::ReplaceWith);
+                }
+            }
+            Edit(edit_command)=>{
+                self.command_bar.handle_edit_command(edit_command);
+                self.search_for_prompt_value("Replace");
+            }
+            System(ToggleSearchOption(option))=>{
+                self.view.toggle_search_option(option);
+                self.search_for_prompt_value("Replace");
+            }
+            Move(Right | Down)=> self.view.search_next(),
+            Move(Up | Left) => self.view.search_prev(),
+            System(Quit| Resize(_) | Redraw | Search | Replace | GotoLine | Save | ToggleLineNumbers | ToggleSoftWrap | ConvertLineEndings | Open | SwitchBuffer | NextBuffer | PreviousBuffer | SplitPane(_) | FocusNextPane | ClosePane | GrowPane | ShrinkPane | FindFile)| Move(_) | Select(_)=>{}
+        }
+    }
+    fn process_command_during_replace_with(&mut self, command: Command){
+        match command{
+            System(Dismiss) =>{
+                self.set_prompt(PromptType::None);
+                self.view.dismiss_search();
+                self.update_message("Replace aborted.");
+            }
+            Edit(InsertNewLine)=>{
+                let replacement = self.command_bar.value();
+                if self.view.enter_replace(&replacement){
+                    self.set_prompt(PromptType::ReplaceConfirm);
+                }else{
+                    self.finish_replace();
+                }
+            }
+            Edit(edit_command)=> self.command_bar.handle_edit_command(edit_command),
+            System(Quit| Resize(_) | Redraw | Search | Replace | GotoLine | Save | ToggleSearchOption(_) | ToggleLineNumbers | ToggleSoftWrap | ConvertLineEndings | Open | SwitchBuffer | NextBuffer | PreviousBuffer | SplitPane(_) | FocusNextPane | ClosePane | GrowPane | ShrinkPane | FindFile)| Move(_) | Select(_)=>{}
+        }
+    }
+    fn process_command_during_replace_confirm(&mut self, command: &Command){
+        //each answer returns whether there is a further match to ask about
+        let has_next = match command{
+            Edit(Insert('y' | 'Y'))=> self.view.replace_current(),
+            Edit(Insert('n' | 'N'))=> self.view.skip_current(),
+            Edit(Insert('a' | 'A'))=>{
+                self.view.replace_all();
+                false
+            }
+            Edit(Insert('q' | 'Q')) | System(Dismiss)=> false,
+            _=> true,
+        };
+        if !has_next{
+            self.finish_replace();
+        }
+    }
+    fn finish_replace(&mut self){
+        let replacements = self.view.exit_replace();
+        self.set_prompt(PromptType::None);
+        let noun = if replacements == 1 { "replacement" } else { "replacements" };
+        self.update_message(&format!("{replacements} {noun} made"));
+    }
+    //end region
+
+    //region: Disk Change Handling
+    fn check_disk_changes(&mut self){
+        if self.in_prompt() || !self.view.is_changed_on_disk(){
+            return;
+        }
+        if self.view.get_status().is_modified{
+            self.set_prompt(PromptType::FileChanged);
+        }else{
+            self.reload();//nothing to lose
+        }
+    }
+    fn process_command_during_file_changed(&mut self, command: &Command){
+        match command{
+            Edit(Insert('r' | 'R'))=>{
+                self.set_prompt(PromptType::None);
+                self.reload();
+            }
+            Edit(Insert('o' | 'O'))=>{
+                self.set_prompt(PromptType::None);
+                self.save(None);
+            }
+            Edit(Insert('c' | 'C')) | System(Dismiss)=>{
+                self.set_prompt(PromptType::None);
+                self.view.ignore_disk_changes();//keep the buffer and stop asking until the file changes again
+                self.update_message("Kept the version in the editor.");
+            }
+            _=>{}
+        }
+    }
+    fn reload(&mut self){
+        match self.view.reload(){
+            Ok(())=>self.update_message("File changed on disk and was reloaded."),
+            Err(err)=>self.update_message(&format!("Error reloading file: {err}")),
+        }
+    }
+    //end region
+
+    //region: Buffer Handling
+    // The active buffer followed by the background buffers, panes not included
+    fn buffer_views(&self) -> impl Iterator<Item = &View> {
+        once(&self.view).chain(&self.buffers)
+    }
+    fn all_views(&self) -> impl Iterator<Item = &View> {
+        self.buffer_views().chain(self.layout.views())
+    }
+    fn all_views_mut(&mut self) -> impl Iterator<Item = &mut View> {
+        once(&mut self.view).chain(&mut self.buffers).chain(self.layout.views_mut())
+    }
+    // Names of all buffers, the active one first, followed by the others in order
+    fn buffer_names(&self) -> Vec<String> {
+        self.buffer_views()
+            .map(|view| {
+                view.get_file_path()
+                    .map_or_else(|| "[No Name]".to_string(), |path| path.to_string_lossy().into_owned())
+            })
+            .collect()
+    }
+    fn load_view(&self, file_name: &str) -> Result<View, Error> {
+        let mut view = View::default();
+        view.resize(self.view_size());
+        view.load(file_name)?;
+        Ok(view)
+    }
+    fn open_file(&mut self, file_name: &str){
+        //switch to the file if it is already open
+        let target = canonicalize(file_name).ok();
+        let shows_file = |view: &&View| {
+            view.get_file_path()
+                .is_some_and(|path| path == Path::new(file_name) || canonicalize(path).ok() == target)
+        };
+        let open_idx = self.buffer_views().position(|view| shows_file(&view));
+        if let Some(idx) = open_idx{
+            self.activate_buffer(idx);
+            return;
+        }
+        //a file shown in another pane is opened as a second view onto the same buffer
+        let loaded = match self.layout.views().find(shows_file){
+            Some(view)=>Ok(view.split()),
+            None=>self.load_view(file_name),
+        };
+        match loaded{
+            Ok(view)=>{
+                let previous = replace(&mut self.view, view);
+                self.buffers.insert(0, previous);
+                self.buffer_activated();
+                self.check_project_config(file_name);
+            }
+            Err(err)=>self.update_message(&format!("ERR: Could not open file {file_name}: {err}")),
+        }
+    }
+    // Settings are read once at startup, tells when a file belongs to a project with other config files
+    fn check_project_config(&mut self, file_name: &str){
+        if !Config::get().applies_to(&Self::project_dir(Some(file_name))){
+            self.update_message(&format!("{file_name}: project config ignored (read at startup only)"));
+        }
+    }
+    // Makes the buffer at idx (see buffer_names) the active one, keeping the cyclic order of all buffers
+    fn activate_buffer(&mut self, idx: usize){
+        if idx == 0 {
+            return;
+        }
+        let mut views: Vec<View> = once(take(&mut self.view))
+            .chain(self.buffers.drain(..))
+            .collect();
+        views.rotate_left(idx);
+        self.buffers = views.split_off(1);
+        if let Some(view) = views.pop(){
+            self.view = view;
+        }
+        self.buffer_activated();
+    }
+    fn buffer_activated(&mut self){
+        self.view.set_needs_redraw(true);
+        let count = self.buffers.len().saturating_add(1);
+        self.update_message(&format!("{} ({count} buffers open)", self.view.get_status().file_name));
+        if self.view.has_newer_swap_file(){
+            self.set_prompt(PromptType::Recover);
+        }
+    }
+    fn process_command_during_open(&mut self, command: Command){
+        match command{
+            System(Dismiss)=>self.set_prompt(PromptType::None),
+            Edit(InsertNewLine)=>{
+                let file_name = expand_home(&self.command_bar.value());
+                self.set_prompt(PromptType::None);
+                if !file_name.is_empty(){
+                    self.open_file(&file_name);
+                }
+            }
+            Edit(Insert('\t'))=>self.complete_path(),
+            Edit(edit_command)=>{
+                self.command_bar.handle_edit_command(edit_command);
+                self.reset_path_completion();
+            }
+            System(Quit | Resize(_) | Redraw | Search | Replace | GotoLine | Save | ToggleSearchOption(_) | ToggleLineNumbers | ToggleSoftWrap | ConvertLineEndings | Open | SwitchBuffer | NextBuffer | PreviousBuffer | SplitPane(_) | FocusNextPane | ClosePane | GrowPane | ShrinkPane | FindFile) | Move(_) | Select(_)=>{}
+        }
+    }
+    fn process_command_during_switch_buffer(&mut self, command: Command){
+        match command{
+            System(Dismiss)=>self.set_prompt(PromptType::None),
+            Edit(InsertNewLine)=>{
+                let target = self.buffer_matches.first().copied();
+                self.set_prompt(PromptType::None);
+                if let Some(idx) = target{
+                    self.activate_buffer(idx);
+                }
+            }
+            Edit(edit_command)=>{
+                self.command_bar.handle_edit_command(edit_command);
+                let query = self.command_bar.value();
+                self.update_buffer_matches(&query);
+            }
+            //arrows choose among the matches, the first one listed is taken
+            Move(Down | Right)=>{
+                if !self.buffer_matches.is_empty(){
+                    self.buffer_matches.rotate_left(1);
+                }
+                self.update_switch_buffer_prompt();
+            }
+            Move(Up | Left)=>{
+                if !self.buffer_matches.is_empty(){
+                    self.buffer_matches.rotate_right(1);
+                }
+                self.update_switch_buffer_prompt();
+            }
+            System(Quit | Resize(_) | Redraw | Search | Replace | GotoLine | Save | ToggleSearchOption(_) | ToggleLineNumbers | ToggleSoftWrap | ConvertLineEndings | Open | SwitchBuffer | NextBuffer | PreviousBuffer | SplitPane(_) | FocusNextPane | ClosePane | GrowPane | ShrinkPane | FindFile) | Move(_) | Select(_)=>{}
+        }
+    }
+    fn update_buffer_matches(&mut self, query: &str){
+        let mut matches: Vec<(usize, usize)> = self
+            .buffer_names()
+            .iter()
+            .enumerate()
+            .filter_map(|(idx, name)| fuzzy_score(query, name).map(|score| (idx, score)))
+            .collect();
+        //the active buffer comes last among equally good matches, switching to it changes nothing
+        matches.sort_by_key(|&(idx, score)| (Reverse(score), idx == 0, idx));
+        self.buffer_matches = matches.into_iter().map(|(idx, _)| idx).collect();
+        self.update_switch_buffer_prompt();
+    }
+    fn update_switch_buffer_prompt(&mut self){
+        let names = self.buffer_names();
+        let listed = self.prompt_list(
+            self.buffer_matches
+                .iter()
+                .filter_map(|&idx| names.get(idx))
+                .map(String::as_str),
+        );
+        self.command_bar.set_prompt(&format!("Switch to [{listed}]: "));
+    }
+    //end region
+
+    //region: Pane Handling
+    fn split_pane(&mut self, direction: SplitDirection){
+        let view = self.view.split();
+        let previous = replace(&mut self.view, view);
+        self.layout.split(direction, Pane::new(previous, take(&mut self.status_bar)));
+        self.arrange_panes();
+    }
+    fn focus_next_pane(&mut self){
+        if !self.layout.is_split(){
+            self.update_message("There is no other pane.");
+            return;
+        }
+        let focused = Pane::new(take(&mut self.view), take(&mut self.status_bar));
+        let (view, status_bar) = self.layout.focus_next(focused).into_parts();
+        self.view = view;
+        self.status_bar = status_bar;
+        self.view.snap_to_buffer();//the buffer may have been edited in another pane
+        self.arrange_panes();
+    }
+    fn close_pane(&mut self){
+        let Some(pane) = self.layout.close_focused() else {
+            self.update_message("Cannot close the last pane.");
+            return;
+        };
+        let (view, status_bar) = pane.into_parts();
+        let closed = replace(&mut self.view, view);
+        self.status_bar = status_bar;
+        if !self.all_views().any(|view| view.shows_same_buffer(&closed)){
+            self.buffers.insert(0, closed);//the buffer stays open in the background
+        }
+        self.view.snap_to_buffer();
+        self.arrange_panes();
+    }
+    fn resize_pane(&mut self, grow: bool){
+        if self.layout.resize_focused(grow){
+            self.arrange_panes();
+        }else{
+            self.update_message("There is no other pane.");
+        }
+    }
+    //end region
+
+    //region: File Finder
+    fn process_command_during_find_file(&mut self, command: Command){
+        match command{
+            System(Dismiss)=>self.set_prompt(PromptType::None),
+            Edit(InsertNewLine)=>{
+                let selected = self.file_list.selected().map(str::to_string);
+                self.set_prompt(PromptType::None);
+                if let Some(path) = selected{
+                    self.open_file(&path);
+                }
+            }
+            Edit(edit_command)=>{
+                self.command_bar.handle_edit_command(edit_command);
+                let query = self.command_bar.value();
+                self.file_finder.update(&query);
+                self.update_file_list();
+            }
+            Move(Down)=>self.file_list.select_next(),
+            Move(Up)=>self.file_list.select_previous(),
+            System(Quit | Resize(_) | Redraw | Search | Replace | GotoLine | Save | ToggleSearchOption(_) | ToggleLineNumbers | ToggleSoftWrap | ConvertLineEndings | Open | SwitchBuffer | NextBuffer | PreviousBuffer | SplitPane(_) | FocusNextPane | ClosePane | GrowPane | ShrinkPane | FindFile) | Move(_) | Select(_)=>{}
+        }
+    }
+    // Walks on through the directory tree, between steps the editor handles input and redraws
+    fn walk_files(&mut self){
+        if self.file_finder.walk_step(){
+            self.scheduler.schedule(Timer::FileWalk, Duration::ZERO);
+        }
+        //files found while walking must not move the selection away from what the user picked
+        self.file_list.update_items(self.file_finder.best_matches(self.file_list.height()));
+        self.update_file_prompt();
+    }
+    fn update_file_list(&mut self){
+        self.file_list.set_items(self.file_finder.best_matches(self.file_list.height()));
+        self.update_file_prompt();
+    }
+    fn update_file_prompt(&mut self){
+        let state = if self.file_finder.is_walking(){
+            " so far"
+        }else if self.file_finder.is_truncated(){
+            ", limit reached"
+        }else{
+            ""
+        };
+        self.command_bar.set_prompt(&format!(
+            "Find file ({}/{}{state}): ",
+            self.file_finder.match_count(),
+            self.file_finder.file_count()
+        ));
+    }
+    //end region
+
+    //region: Path Completion
+    // Completes the path in the open or save prompt. The first Tab fills in what all candidates share, further ones cycle through them.
+    fn complete_path(&mut self){
+        if let Some(completion) = &mut self.path_completion{
+            if let Some(candidate) = completion.cycle(){
+                self.command_bar.set_value(candidate);
+            }
+            return;
+        }
+        let value = self.command_bar.value();
+        let mut completion = PathCompletion::new(&value);
+        let (completed, keep) = match completion.candidates(){
+            [] => {
+                self.update_path_prompt("(no match)");
+                return;
+            }
+            [candidate] => (Some(candidate.clone()), false),//the next Tab continues below a completed directory
+            _ => {
+                let prefix = completion.common_prefix();
+                if prefix.len() > value.len(){
+                    (Some(prefix.to_string()), true)
+                }else{
+                    (completion.cycle().map(str::to_string), true)
+                }
+            }
+        };
+        if let Some(completed) = completed{
+            self.command_bar.set_value(&completed);
+        }
+        self.path_completion = keep.then_some(completion);
+        self.update_path_prompt("");
+    }
+    fn reset_path_completion(&mut self){
+        if self.path_completion.take().is_some(){
+            self.update_path_prompt("");
+        }
+    }
+    // Shows the candidates, or the given note if there are none
+    fn update_path_prompt(&mut self, note: &str){
+        let action = if self.prompt_type == PromptType::Save { "Save as" } else { "Open file" };
+        let prompt = match &self.path_completion{
+            Some(completion)=>format!("{action} [{}]: ", self.prompt_list(completion.names())),
+            None if note.is_empty()=>format!("{action}: "),
+            None=>format!("{action} {note}: "),
+        };
+        self.command_bar.set_prompt(&prompt);
+    }
+    //end region
+
+    //region: Crash Recovery
+    fn update_swap_file(&mut self){
+        let result = self
+            .all_views_mut()
+            .try_for_each(|view| view.update_swap_file(false));
+        if let Err(err) = result{
+            self.update_message(&format!("Error writing swap file: {err}"));
+        }
+        //changes made shortly after the last write are written once the interval has passed
+        match self.all_views().filter_map(View::swap_file_due).min(){
+            Some(due)=>self.scheduler.schedule_at(Timer::SwapFile, due),
+            None=>self.scheduler.cancel(Timer::SwapFile),
+        }
+    }
+    fn process_command_during_recover(&mut self, command: &Command){
+        let result = match command{
+            Edit(Insert('r' | 'R'))=>self.view.recover_swap_file().map(|()| "Unsaved changes recovered."),
+            Edit(Insert('d' | 'D'))=>self.view.discard_swap_file().map(|()| "Unsaved changes discarded."),
+            System(Dismiss)=>{
+                self.view.keep_swap_file();
+                Ok("Swap file kept, edits here are not backed up.")
+            }
+            System(Quit)=>{
+                self.handle_quit_command();//the swap file stays for the next session
+                return;
+            }
+            Move(move_command)=>{
+                self.view.handle_move_command(*move_command);//looking around leaves the swap file alone
+                return;
+            }
+            _=>return,//a decision is required, otherwise the swap file would be overwritten by the next edit
+        };
+        self.set_prompt(PromptType::None);
+        match result{
+            Ok(message)=>self.update_message(message),
+            Err(err)=>self.update_message(&format!("Error reading swap file: {err}")),
+        }
+    }
+    //end region
+
+    //region: Go to Line Prompt Handling
+    fn process_command_during_goto_line(&mut self, command: Command){
+        match command{
+            System(Dismiss)=>self.set_prompt(PromptType::None),
+            Edit(InsertNewLine)=>{
+                let target = self.command_bar.value();
+                self.set_prompt(PromptType::None);
+                if let Err(err) = self.view.goto(&target){
+                    self.update_message(&err);
+                }
+            }
+            Edit(edit_command)=>self.command_bar.handle_edit_command(edit_command),
+            System(Quit | Resize(_) | Redraw | Search | Replace | GotoLine | Save | ToggleSearchOption(_) | ToggleLineNumbers | ToggleSoftWrap | ConvertLineEndings | Open | SwitchBuffer | NextBuffer | PreviousBuffer | SplitPane(_) | FocusNextPane | ClosePane | GrowPane | ShrinkPane | FindFile) | Move(_) | Select(_)=>{}
+        }
+    }
+    //end region
+
     //region: Message and Command Bar
     fn update_message(&mut self, new_message: &str){
-        self.message_bar.update_message(new_message);
+        self.message_bar.update_message(new_message, &mut self.scheduler);
+    }
+    // Joins items for listing them in a prompt, as many as fit while leaving room for typing
+    fn prompt_list<'a>(&self, items: impl Iterator<Item = &'a str>) -> String{
+        const INPUT_WIDTH: usize = 20; // room kept free for typing
+        let available = self.terminal_size.width.saturating_sub(INPUT_WIDTH);
+        let mut listed = String::new();
+        for item in items{
+            if listed.width().saturating_add(item.width()).saturating_add(3) > available{
+                listed.push_str(" …");
+                break;
+            }
+            if !listed.is_empty(){
+                listed.push_str(" | ");
+            }
+            listed.push_str(item);
+        }
+        listed
     }
     // end region
 
@@ -330,17 +1045,47 @@ impl Editor {
     }
 
     fn set_prompt(&mut self, prompt_type: PromptType){
+        if self.prompt_type == PromptType::FindFile{
+            self.file_finder = FileFinder::default();//the list of files is not needed anymore
+            self.scheduler.cancel(Timer::FileWalk);
+            self.view.set_needs_redraw(true);//uncover the part of the view below the file list
+        }
         //command now sets and enters apt prompt, based on prompt_type it sets  up the command bar clears value and sets internal prompt type and trigger redraw of message bar
         match prompt_type{
             PromptType::None=>self.message_bar.set_needs_redraw(true),//ensure message bar redraw properly in next cycle
             PromptType::Save=>self.command_bar.set_prompt("Save as: "),
             PromptType::Search=> {
                 self.view.enter_search();
-                self.command_bar
-                    .set_prompt("Search (Esc to cancel, and Arrows to navigate) : ");
+                let prompt = self.search_prompt("Search", true);
+                self.command_bar.set_prompt(&prompt);
+            }
+            PromptType::Replace=> {
+                self.view.enter_search();
+                let prompt = self.search_prompt("Replace", true);
+                self.command_bar.set_prompt(&prompt);
+            }
+            PromptType::GotoLine=>self
+                .command_bar
+                .set_prompt("Go to line (line[:column], +lines, -lines or %percent): "),
+            PromptType::ReplaceWith=>self.command_bar.set_prompt("Replace with: "),
+            PromptType::Open=>self.command_bar.set_prompt("Open file: "),
+            PromptType::SwitchBuffer=>self.update_buffer_matches(""),
+            PromptType::FindFile=>{
+                self.file_finder = FileFinder::new(Path::new("."));
+                self.walk_files();
             }
+            PromptType::Recover=>self
+                .command_bar
+                .set_prompt("Found unsaved changes from a crashed session. (r)ecover, (d)iscard"),
+            PromptType::FileChanged=>self
+                .command_bar
+                .set_prompt("File changed on disk. (r)eload, (o)verwrite, (c)ancel"),
+            PromptType::ReplaceConfirm=>self
+                .command_bar
+                .set_prompt("Replace this match? (y)es, (n)o, (a)ll, (q)uit"),
         }
         self.command_bar.clear_value();
+        self.path_completion = None;
         self.prompt_type=prompt_type;
     }
     //end region
@@ -349,6 +1094,16 @@ impl Editor {
 
 impl Drop for Editor {
     fn drop(&mut self) {
+        //the panic hook has no access to the buffers, so the emergency dump happens here while the panic unwinds
+        if panicking() {
+            for view in self.all_views_mut() {
+                let _ = view.update_swap_file(true);
+            }
+        } else if self.should_quit {
+            for view in self.all_views_mut() {
+                let _ = view.remove_swap_file(); //unsaved changes were discarded on purpose
+            }
+        }
         let _ = Terminal::terminate();
         if self.should_quit {
             let _ = Terminal::print("Goodbye. \r\n");
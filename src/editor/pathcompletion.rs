@@ -0,0 +1,105 @@
+use std::{cmp::min, env, fs::read_dir, path::Path};
+
+// Completion of the last component of a path typed into a prompt
+#[derive(Default)]
+pub struct PathCompletion {
+    candidates: Vec<String>, // complete prompt values, directories end in '/'
+    dir_len: usize,          // length of the directory part all candidates share
+    next_idx: Option<usize>, // the candidate to fill in next when cycling, None until cycling starts
+}
+
+impl PathCompletion {
+    // Lists the entries of the directory typed so far whose names start with the last component of value.
+    // Hidden entries are only offered if that component starts with a dot.
+    pub fn new(value: &str) -> Self {
+        if value == "~" {
+            return Self::with_candidates(vec!["~/".to_string()], 0);
+        }
+        let (dir, prefix) = value
+            .rfind('/')
+            .map_or(("", value), |idx| value.split_at(idx.saturating_add(1)));
+        let dir_to_read = if dir.is_empty() {
+            ".".to_string()
+        } else {
+            expand_home(dir)
+        };
+        let Ok(entries) = read_dir(dir_to_read) else {
+            return Self::default();
+        };
+        let mut candidates: Vec<String> = entries
+            .filter_map(Result::ok)
+            .filter_map(|entry| {
+                let name = entry.file_name().into_string().ok()?;
+                if !name.starts_with(prefix) || (name.starts_with('.') && !prefix.starts_with('.')) {
+                    return None;
+                }
+                let separator = if entry.path().is_dir() { "/" } else { "" }; //follows symlinks to directories
+                Some(format!("{dir}{name}{separator}"))
+            })
+            .collect();
+        candidates.sort();
+        Self::with_candidates(candidates, dir.len())
+    }
+
+    const fn with_candidates(candidates: Vec<String>, dir_len: usize) -> Self {
+        Self {
+            candidates,
+            dir_len,
+            next_idx: None,
+        }
+    }
+
+    pub fn candidates(&self) -> &[String] {
+        &self.candidates
+    }
+
+    // The candidates without their directory part, for listing them
+    pub fn names(&self) -> impl Iterator<Item = &str> {
+        self.candidates
+            .iter()
+            .map(|candidate| candidate.get(self.dir_len..).unwrap_or(candidate))
+    }
+
+    // The longest beginning shared by all candidates
+    pub fn common_prefix(&self) -> &str {
+        let Some((first, others)) = self.candidates.split_first() else {
+            return "";
+        };
+        let len = others.iter().fold(first.len(), |len, candidate| {
+            let shared = first
+                .chars()
+                .zip(candidate.chars())
+                .take_while(|(a, b)| a == b)
+                .map(|(character, _)| character.len_utf8())
+                .sum();
+            min(len, shared)
+        });
+        &first[..len]
+    }
+
+    // Returns the next candidate, starting over after the last one
+    pub fn cycle(&mut self) -> Option<&str> {
+        let idx = self
+            .next_idx
+            .filter(|&idx| idx < self.candidates.len())
+            .unwrap_or(0);
+        let candidate = self.candidates.get(idx)?;
+        self.next_idx = Some(idx.saturating_add(1));
+        Some(candidate)
+    }
+}
+
+// Replaces a leading ~ by the home directory of the user
+pub fn expand_home(path: &str) -> String {
+    let rest = if path == "~" {
+        ""
+    } else if let Some(rest) = path.strip_prefix("~/") {
+        rest
+    } else {
+        return path.to_string();
+    };
+    env::var("HOME").map_or_else(
+        |_| path.to_string(),
+        |home| Path::new(&home).join(rest).to_string_lossy().into_owned(),
+    )
+}
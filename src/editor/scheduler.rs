@@ -0,0 +1,53 @@
+use std::time::{Duration, Instant};
+
+// Things which have to happen at a certain time, even if no key is pressed
+#[derive(Copy, Clone, Eq, PartialEq, Debug)]
+pub enum Timer {
+    MessageExpiry, // the message bar clears its message
+    SwapFile,      // unsaved changes are due to be written to the swap file
+    DiskCheck,     // the open file is checked for changes made by other processes
+    FileWalk,      // the file finder walks on through the directory tree
+}
+
+// Deadlines of the pending timers, at most one per timer. The event loop waits for input until the earliest one.
+#[derive(Default)]
+pub struct Scheduler {
+    deadlines: Vec<(Timer, Instant)>,
+}
+
+impl Scheduler {
+    // Lets timer fire after delay, replacing its previous deadline
+    pub fn schedule(&mut self, timer: Timer, delay: Duration) {
+        self.schedule_at(timer, Instant::now().checked_add(delay).unwrap_or_else(Instant::now));
+    }
+
+    pub fn schedule_at(&mut self, timer: Timer, deadline: Instant) {
+        self.cancel(timer);
+        self.deadlines.push((timer, deadline));
+    }
+
+    pub fn cancel(&mut self, timer: Timer) {
+        self.deadlines.retain(|&(scheduled, _)| scheduled != timer);
+    }
+
+    // How long to wait for input before the next timer fires, None if there is none
+    pub fn timeout(&self) -> Option<Duration> {
+        self.deadlines
+            .iter()
+            .map(|&(_, deadline)| deadline)
+            .min()
+            .map(|deadline| deadline.saturating_duration_since(Instant::now()))
+    }
+
+    // Removes the timers whose deadline has passed and returns them, earliest first
+    pub fn take_due(&mut self) -> Vec<Timer> {
+        let now = Instant::now();
+        let (mut due, pending): (Vec<_>, Vec<_>) = self
+            .deadlines
+            .iter()
+            .partition(|&&(_, deadline)| deadline <= now);
+        self.deadlines = pending;
+        due.sort_by_key(|&(_, deadline)| deadline);
+        due.into_iter().map(|(timer, _)| timer).collect()
+    }
+}
@@ -0,0 +1,44 @@
+use std::fmt::{self, Display};
+
+#[derive(Copy, Clone, Eq, PartialEq)]
+pub enum SearchOption {
+    Regex,
+    SmartCase,
+    WholeWord,
+}
+
+#[derive(Default, Copy, Clone, Eq, PartialEq)]
+pub struct SearchOptions {
+    pub regex: bool, // interpret the query as regular expression instead of literal text
+    pub smart_case: bool, // ignore case, unless the query contains an uppercase letter
+    pub whole_word: bool, // only match if not surrounded by word characters
+}
+
+impl SearchOptions {
+    pub const fn toggle(&mut self, option: SearchOption) {
+        match option {
+            SearchOption::Regex => self.regex = !self.regex,
+            SearchOption::SmartCase => self.smart_case = !self.smart_case,
+            SearchOption::WholeWord => self.whole_word = !self.whole_word,
+        }
+    }
+}
+
+impl Display for SearchOptions {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        //lists the active options, e.g. to be shown in the prompt
+        let active: Vec<&str> = [
+            (self.regex, "regex"),
+            (self.smart_case, "smart case"),
+            (self.whole_word, "whole word"),
+        ]
+        .iter()
+        .filter(|(is_active, _)| *is_active)
+        .map(|(_, name)| *name)
+        .collect();
+        if active.is_empty() {
+            return Ok(());
+        }
+        write!(formatter, " [{}]", active.join(", "))
+    }
+}
@@ -0,0 +1,43 @@
+use crate::prelude::*;
+use regex::{Regex, RegexBuilder};
+use std::ops::Range;
+
+use super::SearchOptions;
+
+// A compiled search query. Literal queries are escaped, so every query and option can be matched the same way.
+pub struct SearchQuery {
+    regex: Regex,
+    is_empty: bool,
+}
+
+impl SearchQuery {
+    pub fn new(query: &str, options: SearchOptions) -> Result<Self, regex::Error> {
+        let mut pattern = if options.regex {
+            query.to_string()
+        } else {
+            regex::escape(query)
+        };
+        if options.whole_word {
+            //half word boundaries also work for queries starting or ending with non-word characters
+            pattern = format!(r"\b{{start-half}}(?:{pattern})\b{{end-half}}");
+        }
+        let case_insensitive = options.smart_case && !query.chars().any(char::is_uppercase);
+        Ok(Self {
+            regex: RegexBuilder::new(&pattern)
+                .case_insensitive(case_insensitive)
+                .build()?,
+            is_empty: query.is_empty(),
+        })
+    }
+
+    pub const fn is_empty(&self) -> bool {
+        self.is_empty
+    }
+
+    // Finds the leftmost match starting at or after start. Anchors and word boundaries still see the whole haystack.
+    pub fn find_at(&self, haystack: &str, start: ByteIdx) -> Option<Range<ByteIdx>> {
+        self.regex
+            .find_at(haystack, start)
+            .map(|found| found.range())
+    }
+}
@@ -1,7 +1,8 @@
 use crossterm::style::Color;
 
-use super::super::AnnotationType;
+use super::super::{AnnotationType, Config};
 
+#[derive(Clone, Copy, PartialEq, Eq, Default)]
 pub struct Attribute {
     pub foreground: Option<Color>,
     pub background: Option<Color>,
@@ -10,6 +11,19 @@ pub struct Attribute {
 
 impl From<AnnotationType> for Attribute {
     fn from(annotation_type: AnnotationType) -> Self {
+        //colors set in the config files take precedence over the built in ones
+        let config = Config::get();
+        let built_in = Self::built_in(annotation_type);
+        Self {
+            foreground: config.foreground_of(annotation_type).or(built_in.foreground),
+            background: config.background_of(annotation_type).or(built_in.background),
+        }
+    }
+}
+
+impl Attribute {
+    #[allow(clippy::too_many_lines)] //one flat arm per annotation type reads better than splitting the mapping
+    fn built_in(annotation_type: AnnotationType) -> Self {
         //allows conversion of annotation type to attribute, seperating concerns , this will also map string highlights to specific colors
         match annotation_type {
             AnnotationType::Match => Self {
@@ -99,7 +113,31 @@ impl From<AnnotationType> for Attribute {
                     b: 102,
                 }),
                 background: None,
-            }, 
+            },
+            AnnotationType::LineNumber => Self {
+                foreground: Some(Color::Rgb {
+                    r: 110,
+                    g: 110,
+                    b: 110,
+                }),
+                background: None,
+            },
+            AnnotationType::CurrentLineNumber => Self {
+                foreground: Some(Color::Rgb {
+                    r: 255,
+                    g: 215,
+                    b: 0,
+                }),
+                background: None,
+            },
+            AnnotationType::Selection => Self {
+                foreground: None,
+                background: Some(Color::Rgb {
+                    r: 68,
+                    g: 85,
+                    b: 137,
+                }),
+            },
         }
     }
 }
@@ -0,0 +1,29 @@
+use super::Attribute;
+
+// One column of the screen. A wide grapheme fills its own cell and an empty one right of it.
+#[derive(Clone, PartialEq, Eq)]
+pub struct Cell {
+    pub grapheme: String, // empty for the right half of a wide grapheme
+    pub attribute: Attribute,
+    pub inverted: bool,
+}
+
+impl Default for Cell {
+    fn default() -> Self {
+        Self {
+            grapheme: " ".to_string(),
+            attribute: Attribute::default(),
+            inverted: false,
+        }
+    }
+}
+
+impl Cell {
+    pub fn is_continuation(&self) -> bool {
+        self.grapheme.is_empty()
+    }
+
+    pub fn has_style_of(&self, other: &Self) -> bool {
+        self.attribute == other.attribute && self.inverted == other.inverted
+    }
+}
@@ -0,0 +1,110 @@
+use std::ops::Range;
+
+use crate::prelude::*;
+use unicode_segmentation::UnicodeSegmentation;
+use unicode_width::UnicodeWidthStr;
+
+use super::{Attribute, Cell};
+
+// The contents of the whole screen, one cell per column
+#[derive(Default)]
+pub struct Frame {
+    rows: Vec<Vec<Cell>>,
+}
+
+impl Frame {
+    pub fn new(size: Size) -> Self {
+        Self {
+            rows: vec![vec![Cell::default(); size.width]; size.height],
+        }
+    }
+
+    pub fn size(&self) -> Size {
+        Size {
+            height: self.rows.len(),
+            width: self.rows.first().map_or(0, Vec::len),
+        }
+    }
+
+    pub fn row(&self, row: RowIdx) -> &[Cell] {
+        self.rows.get(row).map_or(&[], Vec::as_slice)
+    }
+
+    // Takes over the cells in cols of row from other, which must have the same size
+    pub fn copy_span(&mut self, other: &Self, row: RowIdx, cols: Range<ColIdx>) {
+        if let Some(cells) = self.rows.get_mut(row).and_then(|cells| cells.get_mut(cols.clone()))
+            && let Some(other_cells) = other.row(row).get(cols)
+        {
+            cells.clone_from_slice(other_cells);
+        }
+    }
+
+    // Writes text from origin on, cut off after width columns, and returns the number of columns written.
+    // A wide grapheme which would stick out is left out.
+    pub fn print(
+        &mut self,
+        origin: Position,
+        width: usize,
+        text: &str,
+        attribute: Attribute,
+        inverted: bool,
+    ) -> usize {
+        let end = origin.col.saturating_add(width);
+        let mut col = origin.col;
+        for grapheme in text.graphemes(true) {
+            let cell_count = grapheme.width().min(2);
+            if cell_count == 0 {
+                continue; //the terminal would draw it onto the cell before
+            }
+            if col.saturating_add(cell_count) > end {
+                break;
+            }
+            self.put(Position { row: origin.row, col }, grapheme, cell_count, attribute, inverted);
+            col = col.saturating_add(cell_count);
+        }
+        col.saturating_sub(origin.col)
+    }
+
+    fn put(
+        &mut self,
+        at: Position,
+        grapheme: &str,
+        cell_count: usize,
+        attribute: Attribute,
+        inverted: bool,
+    ) {
+        let Some(cells) = self.rows.get_mut(at.row) else {
+            return;
+        };
+        let end = at.col.saturating_add(cell_count);
+        if end > cells.len() {
+            return;
+        }
+        // wide graphemes losing one of their halves are blanked, the terminal would clear them as well
+        if cells.get(at.col).is_some_and(Cell::is_continuation)
+            && let Some(left) = at.col.checked_sub(1).and_then(|col| cells.get_mut(col))
+        {
+            *left = Cell::default();
+        }
+        if let Some(right) = cells.get_mut(end)
+            && right.is_continuation()
+        {
+            *right = Cell::default();
+        }
+        let cell = Cell {
+            grapheme: grapheme.to_string(),
+            attribute,
+            inverted,
+        };
+        for (idx, target) in cells[at.col..end].iter_mut().enumerate() {
+            *target = if idx == 0 {
+                cell.clone()
+            } else {
+                Cell {
+                    grapheme: String::new(),
+                    ..cell.clone()
+                }
+            };
+        }
+    }
+}
@@ -1,10 +1,18 @@
 mod attribute;
+mod cell;
+mod frame;
+mod screen;
 use crate::prelude::*;
 use attribute::Attribute;
+use cell::Cell;
+use frame::Frame;
+use screen::Screen;
+use std::cell::RefCell;
 use crossterm::cursor::{Hide, MoveTo, Show};
+use crossterm::event::{DisableBracketedPaste, EnableBracketedPaste};
 use crossterm::style::{
     Attribute::{Reset, Reverse},
-    Print, ResetColor, SetBackgroundColor, SetForegroundColor,
+    Print, SetAttribute, SetBackgroundColor, SetForegroundColor,
 };
 use crossterm::terminal::{
     disable_raw_mode, enable_raw_mode, size, Clear, ClearType, DisableLineWrap, EnableLineWrap,
@@ -15,11 +23,17 @@ use std::io::{stdout, Error, Write};
 
 use super::AnnotatedString;
 
+thread_local! {
+    // rows are drawn into the screen's frame and only reach the terminal once presented
+    static SCREEN: RefCell<Screen> = RefCell::new(Screen::default());
+}
+
 //Represent Terminal
 pub struct Terminal;
 
 impl Terminal {
     pub fn terminate() -> Result<(), Error> {
+        Self::disable_bracketed_paste()?;
         Self::leave_alternate_screen()?;
         Self::enable_line_wrap()?;
         Self::show_caret()?;
@@ -30,6 +44,7 @@ impl Terminal {
     pub fn initialize() -> Result<(), Error> {
         enable_raw_mode()?;
         Self::enter_alternate_screen()?;
+        Self::enable_bracketed_paste()?;
         Self::disable_line_wrap()?;
         Self::clear_screen()?;
         Self::execute()?;
@@ -43,12 +58,16 @@ impl Terminal {
         Self::queue_command(LeaveAlternateScreen)?;
         Ok(())
     }
-    pub fn clear_screen() -> Result<(), Error> {
-        Self::queue_command(Clear(ClearType::All))?;
+    pub fn enable_bracketed_paste() -> Result<(), Error> {
+        Self::queue_command(EnableBracketedPaste)?;
+        Ok(())
+    } // pasted text then arrives as one event instead of a key event per character
+    pub fn disable_bracketed_paste() -> Result<(), Error> {
+        Self::queue_command(DisableBracketedPaste)?;
         Ok(())
     }
-    pub fn clear_line() -> Result<(), Error> {
-        Self::queue_command(Clear(ClearType::CurrentLine))?;
+    pub fn clear_screen() -> Result<(), Error> {
+        Self::queue_command(Clear(ClearType::All))?;
         Ok(())
     }
     pub fn move_caret_to(position: Position) -> Result<(), Error> {
@@ -80,57 +99,85 @@ impl Terminal {
         Self::queue_command(Print(string))?;
         Ok(())
     }
-    pub fn print_row(row: RowIdx, line_text: &str) -> Result<(), Error> {
-        Self::move_caret_to(Position { row, col: 0 })?;
-        Self::clear_line()?;
-        Self::print(line_text)?;
-        Ok(())
+    // Prints line_text at origin and blanks the rest of the row up to width, leaving everything right of it untouched.
+    pub fn print_row(origin: Position, width: usize, line_text: &str) {
+        Self::print_parts(origin, width, [(line_text, Attribute::default())], false);
     }
 
     pub fn print_annotated_row(
-        row: RowIdx,
+        origin: Position,
+        width: usize,
         annotated_string: &AnnotatedString,
-    ) -> Result<(), Error> {
-        Self::move_caret_to(Position { row, col: 0 })?;
-        Self::clear_line()?;
+    ) {
+        //annotated parts get the colors of their annotation type, the others the default ones
+        let parts = annotated_string.into_iter().map(|part| {
+            let attribute = part.annotation_type.map(Attribute::from).unwrap_or_default();
+            (part.string, attribute)
+        });
+        Self::print_parts(origin, width, parts, false);
+    }
 
-        //print out each annotated part individually one after other, iterator built helps further
-        annotated_string
-            .into_iter()
-            .try_for_each(|part| -> Result<(), Error> {
-                //similar to foreach except the closure should return a result . if result is error then err is returned
-                if let Some(annotation_type) = part.annotation_type {
-                    let attribute: Attribute = annotation_type.into();
-                    Self::set_attribute(&attribute)?;
-                    //if current text part is annotated create matching attribute and set it on terminal with new helper function
-                }
-                Self::print(part.string)?;
-                Self::reset_color()?;
-                //in either case annotated or not print a part of string and then reset color
-                Ok(()) //Ok for try each
-            })?; //? ensure that any error generated is passed upwards
-        Ok(())
+    pub fn print_inverted_row(origin: Position, width: usize, line_text: &str) {
+        Self::print_parts(origin, width, [(line_text, Attribute::default())], true);
     }
 
-    fn set_attribute(attribute: &Attribute)->Result<(),Error>{
-        if let Some(foreground_color) = attribute.foreground{
+    // Draws the parts one after another into the frame, truncated and padded to width
+    fn print_parts<'a>(
+        origin: Position,
+        width: usize,
+        parts: impl IntoIterator<Item = (&'a str, Attribute)>,
+        inverted: bool,
+    ) {
+        SCREEN.with_borrow_mut(|screen| {
+            let frame = screen.drawn_mut();
+            let mut written = 0;
+            for (text, attribute) in parts {
+                let at = Position { row: origin.row, col: origin.col.saturating_add(written) };
+                let len = frame.print(at, width.saturating_sub(written), text, attribute, inverted);
+                written = written.saturating_add(len);
+            }
+            let padding = width.saturating_sub(written);
+            let at = Position { row: origin.row, col: origin.col.saturating_add(written) };
+            frame.print(at, padding, &" ".repeat(padding), Attribute::default(), inverted);
+        });
+    }
+
+    // Prints what changed since the last call, the caret ends up anywhere
+    pub fn present() -> Result<(), Error> {
+        SCREEN.with_borrow_mut(Screen::present)
+    }
+
+    // Clears the terminal and starts over with a blank frame of the new size
+    pub fn resize(size: Size) -> Result<(), Error> {
+        SCREEN.with_borrow_mut(|screen| screen.reset(size));
+        Self::clear_screen()
+    }
+
+    // Clears the terminal, so that the next present prints every row again
+    pub fn force_repaint() -> Result<(), Error> {
+        SCREEN.with_borrow_mut(Screen::forget_shown);
+        Self::clear_screen()
+    }
+
+    fn set_style(attribute: Attribute, inverted: bool) -> Result<(), Error> {
+        Self::reset_style()?;
+        if let Some(foreground_color) = attribute.foreground {
             Self::queue_command(SetForegroundColor(foreground_color))?;
         }
         if let Some(background_color) = attribute.background {
             Self::queue_command(SetBackgroundColor(background_color))?;
         }
+        if inverted {
+            Self::queue_command(SetAttribute(Reverse))?;
+        }
         Ok(())
     }
 
-    fn reset_color()->Result<(),Error>{
-        Self::queue_command(ResetColor)?;
+    fn reset_style() -> Result<(), Error> {
+        Self::queue_command(SetAttribute(Reset))?;
         Ok(())
     }
 
-    pub fn print_inverted_row(row: RowIdx, line_text: &str) -> Result<(), Error> {
-        let width = Self::size()?.width;
-        Self::print_row(row, &format!("{Reverse}{line_text:width$.width$}{Reset}"))
-    } // print out reverse to start reversing color, print out line text ensuring padding and truncation and then print reset to stop colors.
     pub fn size() -> Result<Size, Error> {
         let (width_u16, height_u16) = size()?;
         #[allow(clippy::as_conversions)]
@@ -0,0 +1,85 @@
+use std::{io::Error, ops::Range};
+
+use crate::prelude::*;
+
+use super::{Cell, Frame, Terminal};
+
+// Changes less than this many columns apart are printed in one go instead of moving the caret in between
+const MAX_GAP: usize = 8;
+
+// What the components drew for the next frame and what the terminal currently shows.
+// Presenting prints only the spans of rows in which both differ.
+#[derive(Default)]
+pub struct Screen {
+    drawn: Frame,
+    shown: Frame,
+}
+
+impl Screen {
+    pub fn drawn_mut(&mut self) -> &mut Frame {
+        &mut self.drawn
+    }
+
+    // Starts over with blank frames, the terminal has to be cleared along with it
+    pub fn reset(&mut self, size: Size) {
+        self.drawn = Frame::new(size);
+        self.shown = Frame::new(size);
+    }
+
+    // Assumes a cleared terminal, so that everything drawn is printed again
+    pub fn forget_shown(&mut self) {
+        self.shown = Frame::new(self.drawn.size());
+    }
+
+    pub fn present(&mut self) -> Result<(), Error> {
+        for row in 0..self.drawn.size().height {
+            for span in self.changed_spans(row) {
+                let cells = self.drawn.row(row).get(span.clone()).unwrap_or_default();
+                Self::print_span(row, span.start, cells)?;
+                self.shown.copy_span(&self.drawn, row, span);
+            }
+        }
+        Ok(())
+    }
+
+    fn changed_spans(&self, row: RowIdx) -> Vec<Range<ColIdx>> {
+        let drawn = self.drawn.row(row);
+        let shown = self.shown.row(row);
+        let mut spans: Vec<Range<ColIdx>> = Vec::new();
+        for (col, cell) in drawn.iter().enumerate() {
+            if shown.get(col) == Some(cell) {
+                continue;
+            }
+            let next_col = col.saturating_add(1);
+            match spans.last_mut() {
+                Some(span) if col <= span.end.saturating_add(MAX_GAP) => span.end = next_col,
+                _ => spans.push(col..next_col),
+            }
+        }
+        // a span may neither start nor end in the middle of a wide grapheme
+        for span in &mut spans {
+            while span.start > 0 && drawn.get(span.start).is_some_and(Cell::is_continuation) {
+                span.start = span.start.saturating_sub(1);
+            }
+            while drawn.get(span.end).is_some_and(Cell::is_continuation) {
+                span.end = span.end.saturating_add(1);
+            }
+        }
+        spans
+    }
+
+    // Prints the cells starting at col, switching the style only where it changes
+    fn print_span(row: RowIdx, col: ColIdx, cells: &[Cell]) -> Result<(), Error> {
+        Terminal::move_caret_to(Position { col, row })?;
+        let mut cells = cells.iter().filter(|cell| !cell.is_continuation()).peekable();
+        while let Some(first) = cells.next() {
+            let mut text = first.grapheme.clone();
+            while let Some(cell) = cells.next_if(|cell| cell.has_style_of(first)) {
+                text.push_str(&cell.grapheme);
+            }
+            Terminal::set_style(first.attribute, first.inverted)?;
+            Terminal::print(&text)?;
+        }
+        Terminal::reset_style()
+    }
+}
@@ -0,0 +1,87 @@
+use super::super::Terminal;
+use super::UIComponent;
+use crate::prelude::*;
+use std::io::Error;
+
+// A list of choices shown above the status line, the selected one is highlighted
+#[derive(Default)]
+pub struct CandidateList {
+    items: Vec<String>,
+    selected: usize,
+    needs_redraw: bool,
+    size: Size,
+}
+
+impl CandidateList {
+    pub fn set_items(&mut self, items: Vec<String>) {
+        self.items = items;
+        self.selected = 0;
+        self.set_needs_redraw(true);
+    }
+
+    pub fn selected(&self) -> Option<&str> {
+        self.items.get(self.selected).map(String::as_str)
+    }
+
+    // Like set_items, but an item picked further down the list stays selected as long as it is listed
+    pub fn update_items(&mut self, items: Vec<String>) {
+        let picked = (self.selected > 0).then(|| self.selected().map(str::to_string)).flatten();
+        self.set_items(items);
+        if let Some(idx) = picked.and_then(|picked| self.items.iter().position(|item| *item == picked)) {
+            self.selected = idx;
+        }
+    }
+
+    pub fn select_next(&mut self) {
+        self.selected = if self.selected.saturating_add(1) < self.items.len() {
+            self.selected.saturating_add(1)
+        } else {
+            0
+        };
+        self.set_needs_redraw(true);
+    }
+
+    pub fn select_previous(&mut self) {
+        self.selected = if self.selected == 0 {
+            self.items.len().saturating_sub(1)
+        } else {
+            self.selected.saturating_sub(1)
+        };
+        self.set_needs_redraw(true);
+    }
+
+    pub const fn height(&self) -> usize {
+        self.size.height
+    }
+}
+
+impl UIComponent for CandidateList {
+    fn set_needs_redraw(&mut self, value: bool) {
+        self.needs_redraw = value;
+    }
+
+    fn needs_redraw(&self) -> bool {
+        self.needs_redraw
+    }
+
+    fn set_size(&mut self, size: Size) {
+        self.size = size;
+    }
+
+    fn draw(&mut self, origin: Position) -> Result<(), Error> {
+        for current_row in 0..self.size.height {
+            let at = Position {
+                row: origin.row.saturating_add(current_row),
+                col: origin.col,
+            };
+            let width = self.size.width;
+            let item = self.items.get(current_row).map_or("", String::as_str);
+            if current_row == self.selected && !item.is_empty() {
+                Terminal::print_inverted_row(at, width, item);
+            } else {
+                Terminal::print_row(at, width, &format!("{item:.width$}")); //long items are cut off at the edge of the pane
+            }
+        }
+        Ok(())
+    }
+}
@@ -1,4 +1,5 @@
 use std::{cmp::min, io::Error};
+use unicode_width::UnicodeWidthStr;
 
 use crate::prelude::*;
 use super::super::{command::Edit, Line, Terminal};
@@ -16,8 +17,24 @@ impl CommandBar{
     pub fn handle_edit_command(&mut self, command: Edit){
         match command{
             Edit::Insert(character)=>self.value.append_char(character),
-            Edit::Delete | Edit::InsertNewLine =>{}
+            Edit::InsertText(text)=>text
+                .chars()
+                .filter(|character| !character.is_control())//prompts hold a single line
+                .for_each(|character| self.value.append_char(character)),
+            Edit::Delete
+            | Edit::DeleteWord
+            | Edit::InsertNewLine
+            | Edit::Undo
+            | Edit::Redo
+            | Edit::Cut
+            | Edit::Copy
+            | Edit::Paste =>{}
             Edit::DeleteBackward=> self.value.delete_last(),
+            Edit::DeleteWordBackward=>{
+                //the caret is always at the end of the value
+                let word_start = self.value.previous_word_start(self.value.grapheme_count());
+                self.value.split(word_start);
+            }
         }
         self.set_needs_redraw(true);
     }
@@ -25,10 +42,10 @@ impl CommandBar{
     pub fn caret_position_col(&self)-> ColIdx{
         let max_width = self
             .prompt
-            .len()
-            .saturating_add(self.value.grapheme_count());
+            .width()
+            .saturating_add(self.value.width());
         min(max_width, self.size.width)
-        //caret's x posn is either width of input + len of prompt (considering ASCII Character)
+        //caret's x posn is either the displayed width of prompt and input (wide characters take two columns)
         // or its width of terminal ,  whichever is smaller
     }
 
@@ -40,6 +57,10 @@ impl CommandBar{
         self.prompt = prompt.to_string();
         self.set_needs_redraw(true);
     }
+    pub fn set_value(&mut self, value: &str){
+        self.value = Line::from(value);
+        self.set_needs_redraw(true);
+    }
     pub fn clear_value(&mut self){
         self.value = Line::default();
         self.set_needs_redraw(true);//allows resetting current value
@@ -59,8 +80,8 @@ impl UIComponent for CommandBar{
         self.size = size;
     }
 
-    fn draw(&mut self, origin: RowIdx) -> Result<(), Error> {
-        let area_for_value = self.size.width.saturating_sub(self.prompt.len());
+    fn draw(&mut self, origin: Position) -> Result<(), Error> {
+        let area_for_value = self.size.width.saturating_sub(self.prompt.width());
         //space between right side of terminal and edge of bar
         let value_end = self.value.width();
         //prefer to show left part of value, so end of visible range we try to access = full width
@@ -73,12 +94,13 @@ impl UIComponent for CommandBar{
             self.prompt,
             self.value.get_visible_graphemes(value_start..value_end)
         );
-        let to_print = if message.len()<=self.size.width{
+        let to_print = if message.width()<=self.size.width{
             //if cant fit what needs to print then dont print anything
             message
         }else{
             String::new()
         };
-        Terminal::print_row(origin, &to_print)
+        Terminal::print_row(origin, self.size.width, &to_print);
+        Ok(())
     }
 }
\ No newline at end of file
@@ -0,0 +1,128 @@
+use super::super::Terminal;
+use super::{UIComponent, View};
+use crate::prelude::*;
+
+mod node;
+use node::Node;
+mod pane;
+pub use pane::Pane;
+mod splitdirection;
+pub use splitdirection::SplitDirection;
+
+const SEPARATOR: &str = "│";
+
+// The tree of panes the screen is divided into.
+// The focused pane is owned by the editor, the leaf standing in for it stays empty.
+#[derive(Default)]
+pub struct Layout {
+    root: Node,
+    separators: Vec<(Position, usize)>, // top and height of the lines between side by side panes
+    needs_redraw: bool,
+}
+
+impl Layout {
+    pub const fn is_split(&self) -> bool {
+        !self.root.is_focused()
+    }
+
+    // Splits the focused pane in two. The given pane takes the top or left half, the focus moves to the other one.
+    pub fn split(&mut self, direction: SplitDirection, pane: Pane) {
+        if let Some(leaf) = self.root.focused_leaf_mut() {
+            *leaf = Node::Split {
+                direction,
+                ratio: 50,
+                first: Box::new(Node::Leaf(Some(Box::new(pane)))),
+                second: Box::new(Node::Leaf(None)),
+            };
+        }
+    }
+
+    // Hands the focus on to the next pane, returning it in exchange for the currently focused one.
+    pub fn focus_next(&mut self, focused: Pane) -> Pane {
+        let mut leaves = Vec::new();
+        self.root.collect_leaves(&mut leaves);
+        let Some(idx) = leaves.iter().position(|leaf| leaf.is_none()) else {
+            return focused;
+        };
+        let next_idx = if idx.saturating_add(1) < leaves.len() {
+            idx.saturating_add(1)
+        } else {
+            0
+        };
+        let Some(next) = leaves.get_mut(next_idx).and_then(|leaf| leaf.take()) else {
+            return focused; //the focused pane is the only one
+        };
+        if let Some(leaf) = leaves.get_mut(idx) {
+            **leaf = Some(Box::new(focused));
+        }
+        *next
+    }
+
+    // Closes the focused pane, returning the one that receives the focus instead. None if it is the last pane.
+    pub fn close_focused(&mut self) -> Option<Pane> {
+        self.root.close_focused()
+    }
+
+    // Returns false if there is no split to resize
+    pub fn resize_focused(&mut self, grow: bool) -> bool {
+        self.root.resize_focused(grow)
+    }
+
+    // Lays the panes out on an area starting at the top left corner and returns the area of the focused pane
+    pub fn arrange(&mut self, size: Size) -> (Position, Size) {
+        self.separators.clear();
+        self.needs_redraw = true;
+        self.root
+            .arrange(Position::default(), size, &mut self.separators)
+            .unwrap_or((Position::default(), size))
+    }
+
+    pub fn views(&self) -> impl Iterator<Item = &View> {
+        let mut panes = Vec::new();
+        self.root.collect_panes(&mut panes);
+        panes.into_iter().map(Pane::view)
+    }
+
+    pub fn views_mut(&mut self) -> impl Iterator<Item = &mut View> {
+        let mut panes = Vec::new();
+        self.root.collect_panes_mut(&mut panes);
+        panes.into_iter().map(Pane::view_mut)
+    }
+
+    // Redraws the panes showing the same buffer as view, e.g. after it was edited
+    pub fn redraw_panes_showing(&mut self, view: &View) {
+        for pane_view in self.views_mut() {
+            if pane_view.shows_same_buffer(view) {
+                pane_view.set_needs_redraw(true);
+            }
+        }
+    }
+
+    pub fn refresh_status(&mut self) {
+        let mut panes = Vec::new();
+        self.root.collect_panes_mut(&mut panes);
+        for pane in panes {
+            pane.refresh_status();
+        }
+    }
+
+    pub fn render(&mut self) {
+        if self.needs_redraw {
+            for &(top, height) in &self.separators {
+                for row in 0..height {
+                    let at = Position {
+                        row: top.row.saturating_add(row),
+                        col: top.col,
+                    };
+                    Terminal::print_row(at, 1, SEPARATOR);
+                }
+            }
+            self.needs_redraw = false;
+        }
+        let mut panes = Vec::new();
+        self.root.collect_panes_mut(&mut panes);
+        for pane in panes {
+            pane.render();
+        }
+    }
+}
@@ -0,0 +1,205 @@
+use super::{Pane, SplitDirection};
+use crate::prelude::*;
+use std::mem::take;
+
+const MIN_RATIO: usize = 10;
+const MAX_RATIO: usize = 90;
+const RATIO_STEP: usize = 5;
+
+pub enum Node {
+    Leaf(Option<Box<Pane>>), // None marks the focused pane, which is owned by the editor
+    Split {
+        direction: SplitDirection,
+        ratio: usize, // share of the first child in percent
+        first: Box<Self>,
+        second: Box<Self>,
+    },
+}
+
+impl Default for Node {
+    fn default() -> Self {
+        Self::Leaf(None)
+    }
+}
+
+impl Node {
+    pub const fn is_focused(&self) -> bool {
+        matches!(self, Self::Leaf(None))
+    }
+
+    pub fn contains_focus(&self) -> bool {
+        match self {
+            Self::Leaf(pane) => pane.is_none(),
+            Self::Split { first, second, .. } => first.contains_focus() || second.contains_focus(),
+        }
+    }
+
+    pub fn focused_leaf_mut(&mut self) -> Option<&mut Self> {
+        match self {
+            Self::Leaf(None) => Some(self),
+            Self::Leaf(Some(_)) => None,
+            Self::Split { first, second, .. } => first
+                .focused_leaf_mut()
+                .or_else(|| second.focused_leaf_mut()),
+        }
+    }
+
+    // Collects the leaves from top left to bottom right
+    pub fn collect_leaves<'a>(&'a mut self, leaves: &mut Vec<&'a mut Option<Box<Pane>>>) {
+        match self {
+            Self::Leaf(pane) => leaves.push(pane),
+            Self::Split { first, second, .. } => {
+                first.collect_leaves(leaves);
+                second.collect_leaves(leaves);
+            }
+        }
+    }
+
+    pub fn collect_panes<'a>(&'a self, panes: &mut Vec<&'a Pane>) {
+        match self {
+            Self::Leaf(pane) => panes.extend(pane.as_deref()),
+            Self::Split { first, second, .. } => {
+                first.collect_panes(panes);
+                second.collect_panes(panes);
+            }
+        }
+    }
+
+    pub fn collect_panes_mut<'a>(&'a mut self, panes: &mut Vec<&'a mut Pane>) {
+        match self {
+            Self::Leaf(pane) => panes.extend(pane.as_deref_mut()),
+            Self::Split { first, second, .. } => {
+                first.collect_panes_mut(panes);
+                second.collect_panes_mut(panes);
+            }
+        }
+    }
+
+    // Removes the focused leaf, its sibling takes over the space. Returns the pane that receives the focus instead.
+    pub fn close_focused(&mut self) -> Option<Pane> {
+        let Self::Split { first, second, .. } = self else {
+            return None;
+        };
+        let sibling = if first.is_focused() {
+            take(second.as_mut())
+        } else if second.is_focused() {
+            take(first.as_mut())
+        } else {
+            return first.close_focused().or_else(|| second.close_focused());
+        };
+        *self = sibling;
+        self.take_first_pane()
+    }
+
+    // Takes the pane out of the top left leaf, which thereby becomes the focused one
+    fn take_first_pane(&mut self) -> Option<Pane> {
+        match self {
+            Self::Leaf(pane) => pane.take().map(|pane| *pane),
+            Self::Split { first, .. } => first.take_first_pane(),
+        }
+    }
+
+    // Moves the border of the innermost split around the focused pane, so that the pane grows or shrinks.
+    // Returns false if the focused pane is not part of this node.
+    pub fn resize_focused(&mut self, grow: bool) -> bool {
+        let Self::Split {
+            ratio,
+            first,
+            second,
+            ..
+        } = self
+        else {
+            return false;
+        };
+        if first.resize_focused(grow) || second.resize_focused(grow) {
+            return true;
+        }
+        let focus_in_first = first.contains_focus();
+        if !focus_in_first && !second.contains_focus() {
+            return false;
+        }
+        let new_ratio = if grow == focus_in_first {
+            ratio.saturating_add(RATIO_STEP)
+        } else {
+            ratio.saturating_sub(RATIO_STEP)
+        };
+        *ratio = new_ratio.clamp(MIN_RATIO, MAX_RATIO);
+        true
+    }
+
+    // Distributes the area among the leaves, the separators between side by side panes are collected as (top, height).
+    // Returns the area of the focused leaf, if it is part of this node.
+    pub fn arrange(
+        &mut self,
+        origin: Position,
+        size: Size,
+        separators: &mut Vec<(Position, usize)>,
+    ) -> Option<(Position, Size)> {
+        match self {
+            Self::Leaf(None) => Some((origin, size)),
+            Self::Leaf(Some(pane)) => {
+                pane.set_area(origin, size);
+                None
+            }
+            Self::Split {
+                direction,
+                ratio,
+                first,
+                second,
+            } => {
+                let (first_size, second_origin, second_size) = match direction {
+                    SplitDirection::Horizontal => {
+                        let first_height = Self::share(size.height, *ratio);
+                        (
+                            Size {
+                                height: first_height,
+                                width: size.width,
+                            },
+                            Position {
+                                row: origin.row.saturating_add(first_height),
+                                col: origin.col,
+                            },
+                            Size {
+                                height: size.height.saturating_sub(first_height),
+                                width: size.width,
+                            },
+                        )
+                    }
+                    SplitDirection::Vertical => {
+                        let first_width = Self::share(size.width.saturating_sub(1), *ratio);
+                        let separator_col = origin.col.saturating_add(first_width);
+                        separators.push((
+                            Position {
+                                row: origin.row,
+                                col: separator_col,
+                            },
+                            size.height,
+                        ));
+                        (
+                            Size {
+                                height: size.height,
+                                width: first_width,
+                            },
+                            Position {
+                                row: origin.row,
+                                col: separator_col.saturating_add(1),
+                            },
+                            Size {
+                                height: size.height,
+                                width: size.width.saturating_sub(first_width).saturating_sub(1),
+                            },
+                        )
+                    }
+                };
+                let first_focus = first.arrange(origin, first_size, separators);
+                let second_focus = second.arrange(second_origin, second_size, separators);
+                first_focus.or(second_focus)
+            }
+        }
+    }
+
+    #[allow(clippy::integer_division)] // panes cover whole cells, the remainder goes to the second child
+    const fn share(length: usize, ratio: usize) -> usize {
+        length.saturating_mul(ratio) / 100
+    }
+}
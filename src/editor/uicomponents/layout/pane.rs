@@ -0,0 +1,61 @@
+use super::super::{StatusBar, UIComponent, View};
+use crate::prelude::*;
+
+// A view that is shown next to the focused one, together with its own status line
+pub struct Pane {
+    view: View,
+    status_bar: StatusBar,
+    origin: Position,
+    size: Size, // including the status line in the last row
+}
+
+impl Pane {
+    pub fn new(view: View, status_bar: StatusBar) -> Self {
+        Self {
+            view,
+            status_bar,
+            origin: Position::default(),
+            size: Size::default(),
+        }
+    }
+
+    pub fn into_parts(self) -> (View, StatusBar) {
+        (self.view, self.status_bar)
+    }
+
+    pub const fn view(&self) -> &View {
+        &self.view
+    }
+
+    pub const fn view_mut(&mut self) -> &mut View {
+        &mut self.view
+    }
+
+    pub fn set_area(&mut self, origin: Position, size: Size) {
+        self.origin = origin;
+        self.size = size;
+        self.view.resize(Size {
+            height: size.height.saturating_sub(1),
+            width: size.width,
+        });
+        self.status_bar.resize(Size {
+            height: 1,
+            width: size.width,
+        });
+    }
+
+    pub fn refresh_status(&mut self) {
+        self.status_bar.update_status(self.view.get_status());
+    }
+
+    pub fn render(&mut self) {
+        if self.size.height == 0 || self.size.width == 0 {
+            return;
+        }
+        self.view.render(self.origin);
+        self.status_bar.render(Position {
+            row: self.origin.row.saturating_add(self.size.height).saturating_sub(1),
+            col: self.origin.col,
+        });
+    }
+}
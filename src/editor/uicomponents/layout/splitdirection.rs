@@ -0,0 +1,5 @@
+#[derive(Copy, Clone, Eq, PartialEq)]
+pub enum SplitDirection {
+    Horizontal, // panes above each other, separated by the status line of the upper one
+    Vertical,   // panes side by side, separated by a vertical line
+}
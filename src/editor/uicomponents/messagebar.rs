@@ -1,10 +1,9 @@
-use std::{io::Error, time::{Duration,Instant}};
+use std::{io::Error, time::Instant};
 
 use crate::prelude::*;
-use super::super::Terminal;
+use super::super::{Config, Scheduler, Terminal, Timer};
 use super::UIComponent;
 
-const DEFAULT_DURATION: Duration = Duration::new(5, 0);
 struct Message{
     text: String,
     time: Instant,
@@ -20,7 +19,7 @@ impl Default for Message{
 }
 impl Message{
     fn is_expired(&self)-> bool{
-        Instant::now().duration_since(self.time) > DEFAULT_DURATION
+        Instant::now().duration_since(self.time) >= Config::get().message_duration
     }
 }
 
@@ -29,10 +28,12 @@ pub struct MessageBar {
     current_message: Message,
     needs_redraw: bool,
     cleared_after_expiry: bool,
+    size: Size,
 }
 
 impl MessageBar {
-    pub fn update_message(&mut self, new_message: &str) {
+    // Shows new_message and has the scheduler wake the editor once it is due to be cleared
+    pub fn update_message(&mut self, new_message: &str, scheduler: &mut Scheduler) {
         
             self.current_message = Message{
                 text: new_message.to_string(),
@@ -40,6 +41,7 @@ impl MessageBar {
             };
             self.cleared_after_expiry = false;
             self.set_needs_redraw(true);
+            scheduler.schedule(Timer::MessageExpiry, Config::get().message_duration);
         }
     }
 
@@ -54,9 +56,11 @@ impl UIComponent for MessageBar {
         self.needs_redraw
     }
     
-    fn set_size(&mut self, _: Size) {}
+    fn set_size(&mut self, size: Size) {
+        self.size = size;
+    }
     
-    fn draw(&mut self, origin: RowIdx) -> Result<(), Error> {
+    fn draw(&mut self, origin: Position) -> Result<(), Error> {
         //upon expiration, we clear the message, to keep track that message is cleared to avoid clearing multiple times
         if self.current_message.is_expired(){
             self.cleared_after_expiry = true;
@@ -66,6 +70,7 @@ impl UIComponent for MessageBar {
         }else{
             &self.current_message.text  
         };
-        Terminal::print_row(origin, message)
+        Terminal::print_row(origin, self.size.width, message);
+        Ok(())
     }
 }
@@ -1,10 +1,14 @@
+mod candidatelist;
 mod commandbar;
+mod layout;
 mod messagebar;
 mod statusbar;
 mod view;
 mod uicomponent;
 
+pub use candidatelist::CandidateList;
 pub use commandbar::CommandBar;
+pub use layout::{Layout, Pane, SplitDirection};
 pub use messagebar::MessageBar;
 pub use statusbar::StatusBar;
 pub use view::View;
@@ -30,7 +30,7 @@ impl UIComponent for StatusBar {
         self.size = size;
     }
 
-    fn draw(&mut self, origin_row: RowIdx) -> Result<(), Error> {
+    fn draw(&mut self, origin: Position) -> Result<(), Error> {
         //Assemble first part of status bar
         let line_count = self.current_status.line_count_to_string();
         let modified_indicator = self.current_status.modified_indicator_to_string();
@@ -42,17 +42,18 @@ impl UIComponent for StatusBar {
         //Assemble back part
         let position_indicator = self.current_status.position_indicator_to_string();
         let file_type= self.current_status.file_type_to_string();
-        let back_part= format!("{file_type} | {position_indicator}"); // assemble filetype with position indicator
+        let line_ending = self.current_status.line_ending_to_string();
+        let back_part= format!("{file_type} | {line_ending} | {position_indicator}"); // assemble filetype with position indicator
         let remainder_len = self.size.width.saturating_sub(beginning.len());
         let status = format!("{beginning}{back_part:>remainder_len$}");
 
-        //print status if it fits else return empty string
+        //print status if it fits, else only its beginning, cut off at the edge of the pane
         let to_print = if status.len() <= self.size.width {
             status
         } else {
-            String::new()
+            beginning
         };
-        Terminal::print_inverted_row(origin_row, &to_print)?;
+        Terminal::print_inverted_row(origin, self.size.width, &to_print);
         Ok(())
     } //rendering errrorsnow pass upwards to the caller of fxn
 }
@@ -18,9 +18,9 @@ pub trait UIComponent {
     fn set_size(&mut self, size: Size);
     // method to update size, implement this too in respective component
 
-    fn render(&mut self, origin_row: RowIdx) {
+    fn render(&mut self, origin: Position) {
         if self.needs_redraw(){
-            if let Err(err) = self.draw(origin_row) {
+            if let Err(err) = self.draw(origin) {
             #[cfg(debug_assertions)]
             {
                 panic!("Could not render component: {err:?}");
@@ -36,6 +36,6 @@ pub trait UIComponent {
         
     } // method to draw this component if it is in need of redrawing
 
-    fn draw(&mut self, origin_row: RowIdx) -> Result<(), Error>;
-    // method to actually draw component, needs to be implemeneted in component
+    fn draw(&mut self, origin: Position) -> Result<(), Error>;
+    // method to actually draw component at origin (its top left corner), needs to be implemeneted in component
 } //new trait for view, messagebar, statusbar
@@ -0,0 +1,88 @@
+use std::{
+    fs::{self, File, OpenOptions},
+    io::{Error, Write},
+    path::{Path, PathBuf},
+    process,
+};
+
+// Replaces the file at path with contents without ever leaving it half written:
+// the contents go to a temporary file in the same directory, are synced to disk and then renamed over the original.
+// Permissions (and on Unix the owner, where allowed) of an existing file are carried over, symlinks are followed.
+// The temporary file has these permissions before anything is written to it, so the contents are never exposed.
+pub fn write_atomically(path: &Path, contents: &[u8]) -> Result<(), Error> {
+    write(path, contents, None)
+}
+
+// Like write_atomically, but the file gets the permissions of template, or is only accessible by the user if there is none.
+// Meant for copies of a file's contents, which must not be readable by anyone who can't read the file itself.
+pub fn write_atomically_like(path: &Path, contents: &[u8], template: Option<&Path>) -> Result<(), Error> {
+    #[cfg(unix)]
+    let mode = {
+        use std::os::unix::fs::PermissionsExt;
+        let template = template.and_then(|template| fs::metadata(template).ok());
+        Some(template.map_or(0o600, |template| template.permissions().mode()))
+    };
+    #[cfg(not(unix))]
+    let mode = {
+        let _ = template; //only Unix has permissions to carry over
+        None
+    };
+    write(path, contents, mode)
+}
+
+// mode replaces the permissions of an existing file, it is ignored outside Unix
+fn write(path: &Path, contents: &[u8], mode: Option<u32>) -> Result<(), Error> {
+    let path = fs::canonicalize(path).unwrap_or_else(|_| path.to_path_buf()); //keep symlinks intact by replacing their target
+    let original = fs::metadata(&path).ok();
+    let temp_path = temp_path_for(&path);
+    let mut options = OpenOptions::new();
+    options.write(true).create_new(true); //never reuse or follow whatever is at the temporary path
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::{OpenOptionsExt, PermissionsExt};
+        //new files get the usual defaults, restricted by the umask
+        let original_mode = original.as_ref().map(|original| original.permissions().mode());
+        options.mode(mode.or(original_mode).unwrap_or(0o666));
+    }
+    let mut file = options.open(&temp_path)?;
+    let result = (|| {
+        if let Some(mode) = mode {
+            #[cfg(unix)]
+            {
+                use std::os::unix::fs::PermissionsExt;
+                file.set_permissions(fs::Permissions::from_mode(mode))?; //the mode given on creation is restricted by the umask
+            }
+            #[cfg(not(unix))]
+            let _ = mode;
+        } else if let Some(original) = &original {
+            file.set_permissions(original.permissions())?; //the mode given on creation is restricted by the umask
+            #[cfg(unix)]
+            {
+                use std::os::unix::fs::{MetadataExt, fchown};
+                //only root may hand a file to another user, keeping our own ownership is the best we can do otherwise
+                let _ = fchown(&file, Some(original.uid()), Some(original.gid()));
+            }
+        }
+        file.write_all(contents)?;
+        file.sync_all()?;
+        fs::rename(&temp_path, &path)
+    })();
+    if result.is_err() {
+        let _ = fs::remove_file(&temp_path); //don't leave the temporary file behind, the original error is what matters
+        return result;
+    }
+    #[cfg(unix)]
+    if let Some(dir) = path.parent()
+        && let Ok(dir) = File::open(dir)
+    {
+        let _ = dir.sync_all(); //persist the rename itself, the file contents are already safe at this point
+    }
+    Ok(())
+}
+
+fn temp_path_for(path: &Path) -> PathBuf {
+    let file_name = path
+        .file_name()
+        .map_or_else(String::new, |name| name.to_string_lossy().into_owned());
+    path.with_file_name(format!(".{file_name}.{}.tmp", process::id()))
+}
@@ -1,20 +1,43 @@
 use super::super::super::AnnotatedString;
+use super::super::super::LineEnding;
+use super::atomicwrite::{write_atomically, write_atomically_like};
 use super::FileInfo;
 use super::Highlighter;
+use super::highlighter::{create_syntax_highlighter, SyntaxHighlighter};
+use super::history::{Change, History};
 use super::Line;
 use super::Location;
+use super::SearchQuery;
 use crate::prelude::*;
-use std::fs::{read_to_string, File};
-use std::io::Error;
-use std::io::Write;
+use ropey::Rope;
+use std::borrow::Cow;
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::fs::{metadata, read_to_string, remove_file};
+use std::time::{Duration, Instant};
+use std::io::{Error, ErrorKind};
+use std::cmp::min;
 use std::ops::Range;
+use std::mem::replace;
 
 
+const SWAP_INTERVAL: Duration = Duration::from_secs(2); // minimum time between two swap file writes
+const MAX_CACHED_LINES: usize = 1000; // the line cache is emptied when it grows beyond this
+
 #[derive(Default)]
+#[allow(clippy::struct_excessive_bools)]//independent flags of the edit and swap file state
 pub struct Buffer {
-     lines: Vec<Line>,
+     text: Rope, // every line ends with '\n', including the last one, so an empty rope holds no lines at all
+     lines: RefCell<HashMap<LineIdx, Line>>, // lines materialized on demand, single line edits update them in place
+     syntax_highlighter: Option<Box<dyn SyntaxHighlighter>>, // kept across frames, edits invalidate it from the edited line on
      dirty: bool,
      file_info: FileInfo,
+     history: History,
+     swap_is_stale: bool, // there are edits the swap file doesn't contain yet
+     swap_written_at: Option<Instant>, // set while a swap file written by us exists
+     swap_attempted_at: Option<Instant>, // last time the swap file was written or failed to be
+     swap_failing: bool, // the last write failed, further failures are not reported again
+     swap_kept: bool, // the swap file of a crashed session is kept for later, so no swap file is written for this buffer
 }// cleaned up buffer defn to have better reasoning
 
 impl Buffer {
@@ -26,62 +49,205 @@ impl Buffer {
         &self.file_info
     }
 
+    //region: Lines
+    // The text of a line without its line break. Lines below the document are empty.
+    fn line_text(&self, idx: LineIdx) -> Cow<'_, str> {
+        if idx >= self.height() {
+            return Cow::Borrowed("");
+        }
+        let line = self.text.line(idx);
+        line.slice(..line.len_chars().saturating_sub(1)).into()
+    }
+
+    // Calls f with the line, which is materialized from the rope unless it is cached already
+    fn with_line<T>(&self, idx: LineIdx, f: impl FnOnce(&Line) -> T) -> Option<T> {
+        if idx >= self.height() {
+            return None;
+        }
+        let mut lines = self.lines.borrow_mut();
+        Self::make_room(&mut lines, idx);
+        let line = lines
+            .entry(idx)
+            .or_insert_with(|| Line::from(&self.line_text(idx)));
+        Some(f(line))
+    }
+
+    fn make_room(lines: &mut HashMap<LineIdx, Line>, idx: LineIdx) {
+        if lines.len() >= MAX_CACHED_LINES && !lines.contains_key(&idx) {
+            lines.clear();
+        }
+    }
+
+    // Calls f with the line without adding it to the cache, for passes over many lines
+    fn with_line_uncached<T>(&self, idx: LineIdx, f: impl FnOnce(&Line) -> T) -> Option<T> {
+        if idx >= self.height() {
+            return None;
+        }
+        if let Some(line) = self.lines.borrow().get(&idx) {
+            return Some(f(line));
+        }
+        Some(f(&Line::from(&self.line_text(idx))))
+    }
+
+    // Drops the cached lines from line_idx on, after an edit moved them to other line indices
+    fn forget_lines_from(&mut self, line_idx: LineIdx) {
+        self.lines.get_mut().retain(|&idx, _| idx < line_idx);
+    }
+
+    // Byte index of the location within its line
+    fn byte_idx(&self, at: Location) -> Option<ByteIdx> {
+        self.with_line(at.line_idx, |line| {
+            line.grapheme_idx_to_byte_idx(min(at.grapheme_idx, line.grapheme_count()))
+        })
+    }
+
+    fn char_idx(&self, line_idx: LineIdx, byte_idx: ByteIdx) -> usize {
+        self.text
+            .byte_to_char(self.text.line_to_byte(line_idx).saturating_add(byte_idx))
+    }
+    //end region
+
     pub fn grapheme_count(&self, idx: LineIdx) -> GraphemeIdx {
-        self.lines.get(idx).map_or(0, Line::grapheme_count)
+        self.with_line(idx, Line::grapheme_count).unwrap_or(0)
     }
     pub fn width_until(&self, idx: LineIdx, until: GraphemeIdx) -> GraphemeIdx {
-        self.lines
-            .get(idx)
-            .map_or(0, |line| line.width_until(until))
+        self.with_line(idx, |line| line.width_until(until))
+            .unwrap_or(0)
     }
     //helper fxns prev calc within view
 
+    // Grapheme ranges of the screen rows of a soft wrapped line. Lines below the document consist of one empty row.
+    pub fn wrap(&self, idx: LineIdx, width: ColIdx) -> Vec<Range<GraphemeIdx>> {
+        self.with_line(idx, |line| line.wrap(width))
+            .unwrap_or_else(|| Line::default().wrap(width))
+    }
+    pub fn grapheme_idx_at(&self, idx: LineIdx, range: Range<GraphemeIdx>, col: ColIdx) -> GraphemeIdx {
+        self.with_line(idx, |line| line.grapheme_idx_at(range, col))
+            .unwrap_or(0)
+    }
+    // Lines consisting only of whitespace separate paragraphs
+    pub fn is_blank(&self, idx: LineIdx) -> bool {
+        self.line_text(idx).trim().is_empty()
+    }
+    pub fn next_word_end(&self, at: Location) -> GraphemeIdx {
+        self.with_line(at.line_idx, |line| line.next_word_end(at.grapheme_idx))
+            .unwrap_or(0)
+    }
+    pub fn previous_word_start(&self, at: Location) -> GraphemeIdx {
+        self.with_line(at.line_idx, |line| line.previous_word_start(at.grapheme_idx))
+            .unwrap_or(0)
+    }
     pub fn get_highlighted_substring(
         &self,
         line_idx: LineIdx,
         range: Range<GraphemeIdx>,
         highlighter: &Highlighter,
     )->Option<AnnotatedString>{
-        self.lines.get(line_idx).map(|line|{
-            line.get_annotated_visible_substr(range, 
-                Some(&highlighter.get_annotations(line_idx)))
+        //syntax annotations come first, so search results and the selection are drawn on top of them
+        let mut annotations = self
+            .syntax_highlighter
+            .as_ref()
+            .and_then(|syntax_highlighter| syntax_highlighter.get_annotations(line_idx))
+            .cloned()
+            .unwrap_or_default();
+        annotations.extend(highlighter.get_annotations(line_idx));
+        self.with_line(line_idx, |line|{
+            line.get_annotated_visible_substr(range, Some(&annotations))
         })
     }// attempt to retrieve correct highlighted strng. gets the annotation from highlighter and calls updated method in line
 
-    pub fn highlight(&self, idx:LineIdx, highlighter: &mut Highlighter){
-        if let Some(line) = self.lines.get(idx){
-            highlighter.highlight(idx,line);
-        }
+    pub fn highlight(&self, idx:LineIdx, columns: Range<ColIdx>, highlighter: &mut Highlighter){
+        self.with_line(idx, |line| highlighter.highlight(idx, line, columns));
     }//new fn to update highlighter
 
+    // Brings the syntax highlighting up to date until the given line. Lines are only highlighted again from the last edit on.
+    pub fn highlight_syntax(&mut self, until: LineIdx) {
+        let Some(mut syntax_highlighter) = self.syntax_highlighter.take() else {
+            return;
+        };
+        for idx in syntax_highlighter.highlighted_lines()..min(until.saturating_add(1), self.height()) {
+            self.with_line_uncached(idx, |line| syntax_highlighter.highlight(idx, line));
+        }
+        self.syntax_highlighter = Some(syntax_highlighter);
+    }
+
+    fn invalidate_syntax_from(&mut self, line_idx: LineIdx) {
+        if let Some(syntax_highlighter) = &mut self.syntax_highlighter {
+            syntax_highlighter.invalidate_from(line_idx);
+        }
+    }
+
     pub fn load(file_name: &str) -> Result<Self, Error> {
         let contents = read_to_string(file_name)?;
-        let mut lines = Vec::new();
-
-        for value in contents.lines() {
-            lines.push(Line::from(value));
-        } // loading content of lines of text file into lines Vector
+        let (text, line_ending, final_newline) = Self::parse(&contents);
 
+        let mut file_info = FileInfo::from(file_name);
+        file_info.set_line_ending(line_ending);
+        file_info.set_final_newline(final_newline);
+        file_info.update_stamp();
         Ok(Self {
-            lines,
-            file_info: FileInfo::from(file_name),
-            dirty: false,
+            text: Rope::from_str(&text),
+            syntax_highlighter: create_syntax_highlighter(file_info.get_file_type()),
+            file_info,
+            ..Self::default()
         })
     }
 
-    pub fn search_forward(&self, query: &str, from: Location)-> Option<Location>{
+    // Normalizes file contents to the buffer text, whose lines all end with '\n', and detects their line ending and whether the last line is terminated
+    fn parse(contents: &str) -> (Cow<'_, str>, LineEnding, bool) {
+        let line_ending = LineEnding::detect(contents);
+        let final_newline = contents.is_empty() || contents.ends_with('\n');
+        //only uniform CRLF files drop the '\r', mixed files keep it to write it back unchanged
+        let mut text = match line_ending {
+            LineEnding::CrLf => Cow::Owned(contents.replace("\r\n", "\n")),
+            LineEnding::Lf | LineEnding::Mixed => Cow::Borrowed(contents),
+        };
+        if !final_newline {
+            text.to_mut().push('\n');
+        }
+        (text, line_ending, final_newline)
+    }
+
+    // The file contents as they are written on save
+    fn contents(&self, file_info: &FileInfo) -> String {
+        let mut contents = String::with_capacity(self.text.len_bytes());
+        self.text.chunks().for_each(|chunk| contents.push_str(chunk));
+        if !file_info.has_final_newline() {
+            contents.pop();
+        }
+        match file_info.get_line_ending().as_str() {
+            "\n" => contents,
+            line_ending => contents.replace('\n', line_ending),
+        }
+    }
+
+    // Quick check whether a line contains a match at all, so lines without one are never materialized during a search.
+    // A line with a match is cached right away, so its text is copied out of the rope only once.
+    fn has_match(&self, idx: LineIdx, query: &SearchQuery) -> bool {
+        if let Some(line) = self.lines.borrow().get(&idx) {
+            return query.find_at(line, 0).is_some();
+        }
+        let text = self.line_text(idx);
+        if query.find_at(&text, 0).is_none() {
+            return false;
+        }
+        let mut lines = self.lines.borrow_mut();
+        Self::make_room(&mut lines, idx);
+        lines.insert(idx, Line::from(&text));
+        true
+    }
+
+    // Returns the range of the next match, which always lies within a single line
+    pub fn search_forward(&self, query: &SearchQuery, from: Location)-> Option<Range<Location>>{
         //revamped search method with some iterator logic 
         if query.is_empty(){
             return None;
         }
         let mut is_first = true; // flag to mark first search as it should be done different
-        for(line_idx, line) in self
-            .lines
-            .iter()//create iterator over all lines
-            .enumerate()//turns it into an iterator over pair (line_idx,line). this is needed to calc location on a match
+        for line_idx in (0..self.height())
             .cycle()//make iterator endless that is if reach end then wrap to start, forever
             .skip(from.line_idx)//from this skip all the linesbefore ones we're interested in
-            .take(self.lines.len().saturating_add(1))//one more to search current line twice(from middle, and from start)
+            .take(self.height().saturating_add(1))//one more to search current line twice(from middle, and from start)
             //now we have all lines in iterator we need, current line, all till end of doc, start of doc, current line
             {
                 let from_grapheme_idx = if is_first{
@@ -90,53 +256,65 @@ impl Buffer {
                 }else{
                     0
                 };
-                if let Some(grapheme_idx) = line.search_forward(query,from_grapheme_idx){
-                    return Some(Location{
-                        grapheme_idx,
-                        line_idx,
-                    });
+                if !self.has_match(line_idx, query) {
+                    continue;
+                }
+                if let Some(grapheme_range) = self
+                    .with_line(line_idx, |line| line.search_forward(query, from_grapheme_idx))
+                    .flatten()
+                {
+                    return Some(Self::line_range_to_locations(line_idx, grapheme_range));
                 }
             }
             None
     }
 
-    pub fn search_backward(&self,query: &str, from: Location)-> Option<Location>{
+    pub fn search_backward(&self,query: &SearchQuery, from: Location)-> Option<Range<Location>>{
         if query.is_empty(){
             return None;
         }
         let mut is_first = true;
-        for (line_idx,line) in self
-            .lines
-            .iter()
-            .enumerate()
+        for line_idx in (0..self.height())
             .rev()
             .cycle()
-            .skip(self.lines.len().saturating_sub(from.line_idx).saturating_sub(1))
-            .take(self.lines.len().saturating_add(1))
+            .skip(self.height().saturating_sub(from.line_idx).saturating_sub(1))
+            .take(self.height().saturating_add(1))
         {
             let from_grapheme_idx = if is_first{
                 is_first=false;
-                from.grapheme_idx
+                Some(from.grapheme_idx)
             }else{
-                line.grapheme_count()
+                None // the end of the line
             };
-            if let Some(grapheme_idx) = line.search_backward(query, from_grapheme_idx){
-                return Some(Location{
-                    grapheme_idx,
-                    line_idx
-                });
+            if !self.has_match(line_idx, query) {
+                continue;
+            }
+            if let Some(grapheme_range) = self
+                .with_line(line_idx, |line| {
+                    line.search_backward(query, from_grapheme_idx.unwrap_or_else(|| line.grapheme_count()))
+                })
+                .flatten()
+            {
+                return Some(Self::line_range_to_locations(line_idx, grapheme_range));
             }
 
         }
         None
     }
 
+    const fn line_range_to_locations(line_idx: LineIdx, grapheme_range: Range<GraphemeIdx>)->Range<Location>{
+        Location{
+            grapheme_idx: grapheme_range.start,
+            line_idx,
+        }..Location{
+            grapheme_idx: grapheme_range.end,
+            line_idx,
+        }
+    }
+
     pub fn save_to_file(&self, file_info: &FileInfo) -> Result<(), Error> {
          if let Some(file_path) = &file_info.get_path() {
-            let mut file = File::create(file_path)?;
-            for line in &self.lines {
-                writeln!(file, "{line}")?;
-            }
+            write_atomically(file_path, self.contents(file_info).as_bytes())?;
         }else{
             #[cfg(debug_assertions)]
             {
@@ -148,70 +326,416 @@ impl Buffer {
     }
 
     pub fn save_as(&mut self, file_name: &str)-> Result<(),Error>{
-        let file_info = FileInfo::from(file_name);
+        let mut file_info = FileInfo::from(file_name);
+        //the new file keeps the format of the current one
+        file_info.set_line_ending(self.file_info.get_line_ending());
+        file_info.set_final_newline(self.file_info.has_final_newline());
         self.save_to_file(&file_info)?;
+        self.remove_swap_file()?; //the swap file belongs to the old name
+        self.swap_kept = false; //a kept one too, the new name has a swap file of its own
+        if file_info.get_file_type() != self.file_info.get_file_type() {
+            self.syntax_highlighter = create_syntax_highlighter(file_info.get_file_type());
+        }
         self.file_info = file_info;
+        self.file_info.update_stamp();
         self.dirty = false;
+        self.history.mark_saved();
         Ok(())
     }
 
     pub fn save(&mut self) -> Result<(), Error> {
         self.save_to_file(&self.file_info)?;
+        self.file_info.update_stamp();
         self.dirty = false;
+        self.history.mark_saved();
+        self.remove_swap_file()
+    }
+
+    // Switches the line ending used on save. The switch, and the removal of carriage returns kept at the end of the lines
+    // of a mixed file, are recorded as edits, so they can be undone.
+    pub fn set_line_ending(&mut self, line_ending: LineEnding) {
+        for line_idx in 0..self.height() {
+            if self.line_text(line_idx).ends_with('\r') {
+                let grapheme_count = self.grapheme_count(line_idx);
+                self.remove_text(
+                    Location {
+                        line_idx,
+                        grapheme_idx: grapheme_count.saturating_sub(1),
+                    },
+                    Location {
+                        line_idx,
+                        grapheme_idx: grapheme_count,
+                    },
+                );
+            }
+        }
+        let previous = self.file_info.get_line_ending();
+        self.file_info.set_line_ending(line_ending);
+        self.record(Change::SetLineEnding {
+            from: previous,
+            to: line_ending,
+        });
+    }
+
+    pub fn is_changed_on_disk(&self) -> bool {
+        self.file_info.is_changed_on_disk()
+    }
+    // Accepts the file on disk as it is now, e.g. when the user chose to keep their version
+    pub fn ignore_disk_changes(&mut self) {
+        self.file_info.update_stamp();
+    }
+
+    //region: Swap file
+    // Keeps the swap file in line with the buffer: writes it if it lacks edits and wasn't attempted recently (unless forced),
+    // and removes it once the buffer has no unsaved changes anymore.
+    // A failed write is retried after the interval, but only the first of several failures in a row is returned.
+    pub fn update_swap_file(&mut self, force: bool) -> Result<(), Error> {
+        if self.swap_kept {
+            return Ok(());
+        }
+        if !self.dirty {
+            self.swap_attempted_at = None; //the next edit is written right away
+            self.swap_failing = false;
+            return self.remove_swap_file();
+        }
+        let attempted_recently = self
+            .swap_attempted_at
+            .is_some_and(|attempted_at| attempted_at.elapsed() < SWAP_INTERVAL);
+        if !self.swap_is_stale || (attempted_recently && !force) {
+            return Ok(());
+        }
+        self.swap_attempted_at = Some(Instant::now());
+        //the swap file holds the file's contents, so it is no more readable than the file itself
+        if let Err(err) = write_atomically_like(
+            &self.file_info.get_swap_path(),
+            self.contents(&self.file_info).as_bytes(),
+            self.file_info.get_path(),
+        ) {
+            let already_reported = replace(&mut self.swap_failing, true);
+            return if already_reported { Ok(()) } else { Err(err) };
+        }
+        self.swap_failing = false;
+        self.swap_is_stale = false;
+        self.swap_written_at = self.swap_attempted_at;
+        Ok(())
+    }
+
+    // When unsaved changes are due to be written to the swap file, None if there are none
+    pub fn swap_file_due(&self) -> Option<Instant> {
+        if self.swap_kept || !self.dirty || !self.swap_is_stale {
+            return None;
+        }
+        Some(
+            self.swap_attempted_at
+                .and_then(|attempted_at| attempted_at.checked_add(SWAP_INTERVAL))
+                .unwrap_or_else(Instant::now),
+        )
+    }
+
+    // Removes the swap file, but only if we wrote it. A swap file left behind by a crashed session stays until the user decides about it.
+    pub fn remove_swap_file(&mut self) -> Result<(), Error> {
+        if self.swap_written_at.take().is_some() {
+            match remove_file(self.file_info.get_swap_path()) {
+                Err(err) if err.kind() != ErrorKind::NotFound => return Err(err),
+                _ => {}
+            }
+        }
         Ok(())
     }
 
+    // Returns true if a swap file exists which is newer than the file itself, i.e. it holds edits which were never saved
+    pub fn has_newer_swap_file(&self) -> bool {
+        if self.swap_written_at.is_some() || self.swap_kept {
+            return false; //it's our own, or the user already decided to keep it
+        }
+        let Ok(swap_modified) = metadata(self.file_info.get_swap_path()).and_then(|data| data.modified()) else {
+            return false;
+        };
+        self.file_info.get_path().is_none_or(|path| {
+            metadata(path)
+                .and_then(|data| data.modified())
+                .is_ok_and(|file_modified| swap_modified >= file_modified) //timestamps may be coarser than the time between two writes
+                || !path.exists()
+        })
+    }
+
+    // Replaces the contents of the buffer with the swap file. This is recorded as an edit, so undo returns to the file on disk.
+    pub fn recover_swap_file(&mut self) -> Result<(), Error> {
+        let contents = read_to_string(self.file_info.get_swap_path())?;
+        let (text, line_ending, final_newline) = Self::parse(&contents);
+        let mut text = text.into_owned();
+        text.pop(); //the last line break is part of the buffer already
+        self.remove_text(Location::default(), self.end_location());
+        self.insert_text(&text, Location::default());
+        self.commit_edit(Location::default(), Location::default(), false);
+        self.file_info.set_line_ending(line_ending);
+        self.file_info.set_final_newline(final_newline);
+        self.dirty = true;
+        self.swap_written_at = Some(Instant::now()); //adopt the swap file, so it is cleaned up on save
+        Ok(())
+    }
+
+    pub fn discard_swap_file(&mut self) -> Result<(), Error> {
+        self.swap_written_at = None;
+        remove_file(self.file_info.get_swap_path())
+    }
+
+    // Leaves the swap file of a crashed session as it is, to be recovered in a later session. Edits are then not written to a swap file.
+    pub const fn keep_swap_file(&mut self) {
+        self.swap_kept = true;
+    }
+    //end region
+
     pub fn is_empty(&self) -> bool {
-        self.lines.is_empty()
+        self.text.len_bytes() == 0
     }
     pub const fn is_file_loaded(&self) -> bool {
         self.file_info.has_path()
     }
     pub fn height(&self) -> LineIdx {
-        self.lines.len()
+        self.text.len_lines().saturating_sub(1) //ropey counts the empty line behind the last line break
     }
 
+    //region: Editing
     pub fn insert_char(&mut self, character: char, at: Location) {
-       debug_assert!(at.line_idx <= self.height());
-       
-        if at.line_idx == self.height() {
-            self.lines.push(Line::from(&character.to_string()));
-            self.dirty = true;
-        }
-        // add new line at edge of document
-        else if let Some(line) = self.lines.get_mut(at.line_idx) {
-            line.insert_char(character, at.grapheme_idx);
-            self.dirty = true;
-        } // if in document middle let line handle the insertion
+        debug_assert!(at.line_idx <= self.height());
+        self.insert_text(&character.to_string(), at);
     }
+
     pub fn delete(&mut self, at: Location) {
-        if let Some(line) = self.lines.get(at.line_idx) {
-            if at.grapheme_idx >= line.grapheme_count()
-                && self.height() > at.line_idx.saturating_add(1)
-            {
-                // checking if we are at end of current line and next line exists
-                let next_line = self.lines.remove(at.line_idx.saturating_add(1));
-                #[allow(clippy::indexing_slicing)]
-                self.lines[at.line_idx].append(&next_line);
-                self.dirty = true;
-            } else if at.grapheme_idx < line.grapheme_count() {
-                #[allow(clippy::indexing_slicing)]
-                self.lines[at.line_idx].delete(at.grapheme_idx);
-                self.dirty = true;
+        if at.line_idx < self.height() {
+            let grapheme_count = self.grapheme_count(at.line_idx);
+            if at.grapheme_idx >= grapheme_count && self.height() > at.line_idx.saturating_add(1) {
+                // checking if we are at end of current line and next line exists, if so join them
+                let next_line_start = Location {
+                    line_idx: at.line_idx.saturating_add(1),
+                    grapheme_idx: 0,
+                };
+                self.remove_text(at, next_line_start);
+            } else if at.grapheme_idx < grapheme_count {
+                let next_grapheme = Location {
+                    line_idx: at.line_idx,
+                    grapheme_idx: at.grapheme_idx.saturating_add(1),
+                };
+                self.remove_text(at, next_grapheme);
             }
         }
     }
 
     pub fn insert_newline(&mut self, at: Location) {
-        if at.line_idx == self.height() {
-            self.lines.push(Line::default());
-            self.dirty = true;
+        self.insert_text("\n", at);
+    }
+
+    // Inserts text which may span multiple lines and returns the location right behind it.
+    // Inserting at the line below the document appends new lines, a trailing newline then keeps the location below the document.
+    pub fn insert_text(&mut self, text: &str, at: Location) -> Location {
+        debug_assert!(at.line_idx <= self.height());
+        if text.is_empty() {
+            return at;
+        }
+        if at.line_idx < self.height() {
+            let to = self.insert_text_raw(text, at);
+            self.record(Change::Insert {
+                from: at,
+                to,
+                text: text.to_string(),
+            });
+            return to;
+        }
+        //at edge of document: turn insertion into an append to the last line
+        let ends_with_newline = text.ends_with('\n');
+        let text = text.strip_suffix('\n').unwrap_or(text);
+        let (from, text) = if let Some(last_idx) = self.height().checked_sub(1) {
+            (
+                Location {
+                    line_idx: last_idx,
+                    grapheme_idx: self.grapheme_count(last_idx),
+                },
+                format!("\n{text}"),
+            )
+        } else {
+            //empty document: the text becomes the first lines, the line break ending the last one is part of the recorded insertion
+            let text = format!("{text}\n");
+            let to = self.insert_text_raw(&text, Location::default());
+            self.record(Change::Insert {
+                from: Location::default(),
+                to,
+                text,
+            });
+            let last_idx = self.height().saturating_sub(1);
+            return if ends_with_newline {
+                to
+            } else {
+                Location {
+                    line_idx: last_idx,
+                    grapheme_idx: self.grapheme_count(last_idx),
+                }
+            };
+        };
+        let mut to = self.insert_text_raw(&text, from);
+        if !text.is_empty() {
+            self.record(Change::Insert { from, to, text });
+        }
+        if ends_with_newline {
+            to = Location {
+                line_idx: self.height(),
+                grapheme_idx: 0,
+            };
+        }
+        to
+    }
+
+    // Removes the text between from (inclusive) and to (exclusive) and returns it, lines separated by '\n'.
+    pub fn remove_text(&mut self, from: Location, to: Location) -> String {
+        let to = min(to, self.end_location()); // the line below the document has no text to remove
+        let text = self.remove_text_raw(from, to);
+        if !text.is_empty() {
+            self.record(Change::Remove {
+                from,
+                to,
+                text: text.clone(),
+            });
+        }
+        text
+    }
+
+    // Returns the text between from (inclusive) and to (exclusive), lines separated by '\n'.
+    pub fn get_text(&self, from: Location, to: Location) -> String {
+        let to = min(to, self.end_location());
+        let (Some(start), Some(end)) = (self.byte_idx(from), self.byte_idx(to)) else {
+            return String::new();
+        };
+        let start = self.char_idx(from.line_idx, start);
+        let end = self.char_idx(to.line_idx, end);
+        if start >= end {
+            return String::new();
+        }
+        self.text.slice(start..end).to_string()
+    }
+
+    // Location right behind the last grapheme of the document.
+    fn end_location(&self) -> Location {
+        let line_idx = self.height().saturating_sub(1);
+        Location {
+            line_idx,
+            grapheme_idx: self.grapheme_count(line_idx),
+        }
+    }
+
+    fn record(&mut self, change: Change) {
+        self.history.record(change);
+        self.dirty = true;
+        self.swap_is_stale = true;
+    }
+
+    // Below the document, at the end of the text, only lines ending with a line break are inserted
+    fn insert_text_raw(&mut self, text: &str, at: Location) -> Location {
+        let (byte_idx, tail_len) = if at.line_idx == self.height() {
+            debug_assert!(text.ends_with('\n'));
+            (0, 0)
+        } else {
+            let Some(found) = self.with_line(at.line_idx, |line| {
+                let grapheme_idx = min(at.grapheme_idx, line.grapheme_count());
+                (
+                    line.grapheme_idx_to_byte_idx(grapheme_idx),
+                    line.grapheme_count().saturating_sub(grapheme_idx), //graphemes behind the insertion
+                )
+            }) else {
+                return at;
+            };
+            found
+        };
+        self.text.insert(self.char_idx(at.line_idx, byte_idx), text);
+        self.invalidate_syntax_from(at.line_idx);
+        let line_breaks = text.matches('\n').count();
+        if line_breaks == 0 {
+            if let Some(line) = self.lines.get_mut().get_mut(&at.line_idx) {
+                line.insert_str(byte_idx, text);
+            }
+        } else {
+            self.forget_lines_from(at.line_idx);
+        }
+        let line_idx = at.line_idx.saturating_add(line_breaks);
+        Location {
+            grapheme_idx: self.grapheme_count(line_idx).saturating_sub(tail_len),
+            line_idx,
+        }
+    }
+
+    // Removing up to the line below the document removes the line break ending the last line, too
+    fn remove_text_raw(&mut self, from: Location, to: Location) -> String {
+        debug_assert!(from.line_idx <= to.line_idx);
+        if to.line_idx > self.height() || from.line_idx > to.line_idx {
+            return String::new();
+        }
+        let to_byte_idx = if to.line_idx == self.height() {
+            Some(0)
+        } else {
+            self.byte_idx(to)
+        };
+        let (Some(from_byte_idx), Some(to_byte_idx)) = (self.byte_idx(from), to_byte_idx) else {
+            return String::new();
+        };
+        let start = self.char_idx(from.line_idx, from_byte_idx);
+        let end = self.char_idx(to.line_idx, to_byte_idx);
+        if start >= end {
+            return String::new();
+        }
+        let removed = self.text.slice(start..end).to_string();
+        self.text.remove(start..end);
+        self.invalidate_syntax_from(from.line_idx);
+        if from.line_idx == to.line_idx {
+            if let Some(line) = self.lines.get_mut().get_mut(&from.line_idx) {
+                line.remove(from_byte_idx..to_byte_idx);
+            }
+        } else {
+            self.forget_lines_from(from.line_idx);
+        }
+        removed
+    }
+    //end region
+
+    //region: Undo & Redo
+    pub fn commit_edit(&mut self, caret_before: Location, caret_after: Location, mergeable: bool) {
+        self.history.commit(caret_before, caret_after, mergeable);
+    }
+
+    // Reverts the last step and returns the caret location from before it was made.
+    pub fn undo(&mut self) -> Option<Location> {
+        let step = self.history.undo()?;
+        for change in step.changes.iter().rev() {
+            match change {
+                Change::Insert { from, to, .. } => {
+                    self.remove_text_raw(*from, *to);
+                }
+                Change::Remove { from, text, .. } => {
+                    self.insert_text_raw(text, *from);
+                }
+                Change::SetLineEnding { from, .. } => self.file_info.set_line_ending(*from),
+            }
+        }
+        self.dirty = !self.history.is_at_saved_state();
+        self.swap_is_stale = true;
+        Some(step.caret_before)
+    }
+
+    // Reapplies the last undone step and returns the caret location from after it was made.
+    pub fn redo(&mut self) -> Option<Location> {
+        let step = self.history.redo()?;
+        for change in &step.changes {
+            match change {
+                Change::Insert { from, text, .. } => {
+                    self.insert_text_raw(text, *from);
+                }
+                Change::Remove { from, to, .. } => {
+                    self.remove_text_raw(*from, *to);
+                }
+                Change::SetLineEnding { to, .. } => self.file_info.set_line_ending(*to),
+            }
         }
-        //if at end of doc insert new line
-        else if let Some(line) = self.lines.get_mut(at.line_idx) {
-            let new = line.split(at.grapheme_idx);
-            self.lines.insert(at.line_idx.saturating_add(1), new);
-            self.dirty = true;
-        } //if in mid of doc, split current line and add splitted to self.lines at proper index
+        self.dirty = !self.history.is_at_saved_state();
+        self.swap_is_stale = true;
+        Some(step.caret_after)
     }
+    //end region
 }
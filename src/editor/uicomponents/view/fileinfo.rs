@@ -1,13 +1,17 @@
-use super::super::super::FileType;
+use super::super::super::{FileType, LineEnding};
+use super::FileStamp;
 use std::{
     fmt::{self, Display},
     path::{Path, PathBuf}, //Internal Data structure to represent path
 };
 
-#[derive(Debug, Default)]
+#[derive(Debug)]
 pub struct FileInfo {
     path: Option<PathBuf>,
     file_type: FileType,
+    line_ending: LineEnding,
+    final_newline: bool, // whether the last line is terminated by a line ending
+    stamp: Option<FileStamp>, // the state of the file on disk when it was last loaded or saved
 }
 
 impl FileInfo {
@@ -15,7 +19,7 @@ impl FileInfo {
         let path = PathBuf::from(file_name);
         let file_type = if path
             .extension()
-            .map_or(false, |ext| ext.eq_ignore_ascii_case("rs"))
+            .is_some_and(|ext| ext.eq_ignore_ascii_case("rs"))
         {
             FileType::Rust
         } else {
@@ -25,6 +29,7 @@ impl FileInfo {
         Self {
             path: Some(path),
             file_type,
+            ..Self::default()
         }
     }
     pub fn get_path(&self) -> Option<&Path> {
@@ -36,6 +41,55 @@ impl FileInfo {
     pub const fn get_file_type(&self) -> FileType {
         self.file_type
     }
+    pub const fn get_line_ending(&self) -> LineEnding {
+        self.line_ending
+    }
+    pub const fn set_line_ending(&mut self, line_ending: LineEnding) {
+        self.line_ending = line_ending;
+    }
+    // Remembers the current state of the file on disk, to be called after loading or saving it
+    pub fn update_stamp(&mut self) {
+        self.stamp = self.path.as_deref().and_then(FileStamp::of);
+    }
+    // Returns true if another process modified the file since it was last loaded or saved. A deleted file doesn't count, saving recreates it.
+    pub fn is_changed_on_disk(&self) -> bool {
+        self.stamp.is_some_and(|stamp| {
+            self.path
+                .as_deref()
+                .and_then(FileStamp::of)
+                .is_some_and(|current| current != stamp)
+        })
+    }
+    // The crash recovery file lives next to the file as `.name.pikaswp`, unnamed buffers use the working directory
+    pub fn get_swap_path(&self) -> PathBuf {
+        self.path.as_deref().map_or_else(
+            || PathBuf::from(".unnamed.pikaswp"),
+            |path| {
+                let name = path
+                    .file_name()
+                    .map_or_else(String::new, |name| name.to_string_lossy().into_owned());
+                path.with_file_name(format!(".{name}.pikaswp"))
+            },
+        )
+    }
+    pub const fn has_final_newline(&self) -> bool {
+        self.final_newline
+    }
+    pub const fn set_final_newline(&mut self, final_newline: bool) {
+        self.final_newline = final_newline;
+    }
+}
+
+impl Default for FileInfo {
+    fn default() -> Self {
+        Self {
+            path: None,
+            file_type: FileType::default(),
+            line_ending: LineEnding::default(),
+            final_newline: true, // new files end with a newline
+            stamp: None,
+        }
+    }
 }
 
 impl Display for FileInfo {
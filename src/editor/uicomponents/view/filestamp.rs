@@ -0,0 +1,18 @@
+use std::{fs, path::Path, time::SystemTime};
+
+// What we know about a file on disk, used to notice when another process changes it
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FileStamp {
+    modified: SystemTime,
+    size: u64,
+}
+
+impl FileStamp {
+    pub fn of(path: &Path) -> Option<Self> {
+        let metadata = fs::metadata(path).ok()?;
+        Some(Self {
+            modified: metadata.modified().ok()?,
+            size: metadata.len(),
+        })
+    }
+}
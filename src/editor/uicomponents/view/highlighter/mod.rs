@@ -1,16 +1,19 @@
 
-use super::super::super::{Annotation, AnnotationType,FileType, Line};
+use super::super::super::{Annotation, AnnotationType,FileType, Line, SearchQuery};
 use crate::prelude::*;
+use std::ops::Range;
 
 mod syntaxhighlighter;
 mod searchresulthighlighter;
 mod rustsyntaxhighlighter;
+mod selectionhighlighter;
 
 use searchresulthighlighter::SearchResultHighlighter;
-use syntaxhighlighter::SyntaxHighlighter;
+use selectionhighlighter::SelectionHighlighter;
+pub use syntaxhighlighter::SyntaxHighlighter;
 use rustsyntaxhighlighter::RustSyntaxHighlighter;
 
-fn create_syntax_highlighter(file_type: FileType)-> Option<Box<dyn SyntaxHighlighter>>{
+pub fn create_syntax_highlighter(file_type: FileType)-> Option<Box<dyn SyntaxHighlighter>>{
     //factory function to create syntax highligher based on filetype
 
     match file_type{
@@ -20,50 +23,51 @@ fn create_syntax_highlighter(file_type: FileType)-> Option<Box<dyn SyntaxHighlig
 }
 
 
+// Highlights what is specific to a single frame. Syntax highlighting is kept by the buffer across frames.
 #[derive(Default)]
+#[allow(clippy::struct_field_names)]//each field is one of the concrete highlighters
 pub struct Highlighter<'a> {
-    syntax_highlighter: Option<Box<dyn SyntaxHighlighter>>,
     search_result_highlighter: Option<SearchResultHighlighter<'a>>,
+    selection_highlighter: Option<SelectionHighlighter>,
 }
 
 impl<'a> Highlighter<'a> {
     pub fn new(
-        matched_word: Option<&'a str>,
+        query: Option<&'a SearchQuery>,
         selected_match: Option<Location>,
-        file_type: FileType,
+        selection: Option<Range<Location>>,
     )-> Self{
-        let search_result_highlighter = matched_word
-            .map(|matched_word| SearchResultHighlighter::new(matched_word,selected_match));
+        let search_result_highlighter = query
+            .map(|query| SearchResultHighlighter::new(query,selected_match));
         Self{
-            syntax_highlighter: create_syntax_highlighter(file_type),
             search_result_highlighter,
+            selection_highlighter: selection.map(SelectionHighlighter::new),
         }
     }   
 
     pub fn get_annotations(&self, idx: LineIdx) -> Vec<Annotation> {
         let mut result = Vec::new();
 
-        if let Some(syntax_highlighter)= &self.syntax_highlighter{
-            if let Some(annotations) = syntax_highlighter.get_annotations(idx){
-                result.extend(annotations.iter().copied());
-                //Merge the annotations by both highlighters to one vector i.e result
-            }
+        if let Some(search_result_highlighter) = &self.search_result_highlighter
+            && let Some(annotations) = search_result_highlighter.get_annotations(idx){
+            result.extend(annotations.iter().copied());
+            //Merge the annotations by both highlighters to one vector i.e result
         }
 
-        if let Some(search_result_highlighter) = &self.search_result_highlighter{
-            if let Some(annotations) = search_result_highlighter.get_annotations(idx){
-                result.extend(annotations.iter().copied());
-            }
+        if let Some(selection_highlighter) = &self.selection_highlighter
+            && let Some(annotations) = selection_highlighter.get_annotations(idx){
+            result.extend(annotations.iter().copied());//added last so the selection is drawn on top
         }
         result
     }
 
-    pub fn highlight(&mut self, idx: LineIdx,line: &Line){
-        if let Some(syntax_highlighter) = &mut self.syntax_highlighter {
-            syntax_highlighter.highlight(idx, line);
-        }
+    // Highlights the line, search results only within the given columns, which are those on screen
+    pub fn highlight(&mut self, idx: LineIdx,line: &Line, columns: Range<ColIdx>){
         if let Some(search_result_highlighter) = &mut self.search_result_highlighter {
-            search_result_highlighter.highlight(idx, line);
+            search_result_highlighter.highlight_visible(idx, line, line.byte_range_of_columns(columns));
+        }
+        if let Some(selection_highlighter) = &mut self.selection_highlighter {
+            selection_highlighter.highlight(idx, line);
         }
         //highlighting now means invoke corresponding highlighter functions  on each concrete highlighter
     }   
@@ -66,9 +66,18 @@ const KNOWN_VALUES: [&str; 6] = ["Some", "None", "true", "false", "Ok", "Err"];
 use super::{Annotation, AnnotationType, Line, SyntaxHighlighter};
 use crate::prelude::*;
 
+// The state carried from the end of one line into the next
+#[derive(Default, Clone, Copy)]
+struct Checkpoint {
+    ml_comment_balance: usize,
+    in_ml_string: bool,
+}
+
+// Lives as long as its buffer: lines are highlighted once and only highlighted again from an edited line on
 #[derive(Default)]
 pub struct RustSyntaxHighlighter {
     highlights: Vec<Vec<Annotation>>,
+    checkpoints: Vec<Checkpoint>, // the state behind each highlighted line, highlighting resumes from there after an edit
     ml_comment_balance: usize,
     in_ml_string: bool,
 }
@@ -163,6 +172,9 @@ impl RustSyntaxHighlighter {
 }
 impl SyntaxHighlighter for RustSyntaxHighlighter {
     fn highlight(&mut self, idx: LineIdx, line: &Line) {
+        if idx < self.highlights.len() {
+            return; //still up to date
+        }
         debug_assert_eq!(idx, self.highlights.len());
         let mut result = Vec::new();
         let mut iterator = line.split_word_bound_indices().peekable();
@@ -201,14 +213,30 @@ impl SyntaxHighlighter for RustSyntaxHighlighter {
                     iterator.next();
                     //for any case where word is still part of previous annotation, we want to consume and discard next word.
                 }
-            };
+            }
         }
         self.highlights.push(result);
+        self.checkpoints.push(Checkpoint {
+            ml_comment_balance: self.ml_comment_balance,
+            in_ml_string: self.in_ml_string,
+        });
     }
 
     fn get_annotations(&self, idx: LineIdx) -> Option<&Vec<Annotation>> {
         self.highlights.get(idx)
     }
+
+    fn highlighted_lines(&self) -> LineIdx {
+        self.highlights.len()
+    }
+
+    fn invalidate_from(&mut self, idx: LineIdx) {
+        self.highlights.truncate(idx);
+        self.checkpoints.truncate(idx);
+        let checkpoint = self.checkpoints.last().copied().unwrap_or_default();
+        self.ml_comment_balance = checkpoint.ml_comment_balance;
+        self.in_ml_string = checkpoint.in_ml_string;
+    }
 }
 
 // use a helper fxn for taking remaining string , annotation type to apply to next word, validator fxn f. if fxn returns true, we annotate the word,
@@ -221,15 +249,15 @@ fn annotate_next_word<F>(
 where
     F: Fn(&str) -> bool,
 {
-    if let Some(word) = string.split_word_bounds().next() {
-        if validator(word) {
-            //only new thing in fn is calling validator fn which is pased as fn argument
-            return Some(Annotation {
-                annotation_type,
-                start: 0,
-                end: word.len(),
-            });
-        }
+    if let Some(word) = string.split_word_bounds().next()
+        && validator(word)
+    {
+        //only new thing in fn is calling validator fn which is pased as fn argument
+        return Some(Annotation {
+            annotation_type,
+            start: 0,
+            end: word.len(),
+        });
     }
     None
 }
@@ -273,14 +301,14 @@ fn annotate_char(string: &str) -> Option<Annotation> {
 
 fn annotate_lifetime_specifier(string: &str) -> Option<Annotation> {
     let mut iter = string.split_word_bound_indices();
-    if let Some((_, "\'")) = iter.next() {
-        if let Some((idx, next_word)) = iter.next() {
-            return Some(Annotation {
-                annotation_type: AnnotationType::LifeTimeSpecifier,
-                start: 0,
-                end: idx.saturating_add(next_word.len()),
-            });
-        }
+    if let Some((_, "\'")) = iter.next()
+        && let Some((idx, next_word)) = iter.next()
+    {
+        return Some(Annotation {
+            annotation_type: AnnotationType::LifeTimeSpecifier,
+            start: 0,
+            end: idx.saturating_add(next_word.len()),
+        });
     }
     None
 }
@@ -310,10 +338,10 @@ fn is_valid_number(word: &str) -> bool {
     let mut chars = word.chars();
 
     //checking first character
-    if let Some(first_char) = chars.next() {
-        if !first_char.is_ascii_digit() {
-            return false; //number must start with digit
-        }
+    if let Some(first_char) = chars.next()
+        && !first_char.is_ascii_digit()
+    {
+        return false; //number must start with digit
     }
 
     let mut seen_dot = false;
@@ -1,69 +1,68 @@
-use std::collections::HashMap;
+use std::{collections::HashMap, ops::Range};
 
-use super::{syntaxhighlighter::SyntaxHighlighter, Annotation, AnnotationType, Line};
+use super::{syntaxhighlighter::SyntaxHighlighter, Annotation, AnnotationType, Line, SearchQuery};
 use crate::prelude::*;
 
-#[derive(Default)]
+const MATCH_LOOKBEHIND: ByteIdx = 1024; // how far left of the visible part matches may start
+
 pub struct SearchResultHighlighter<'a> {
-    matched_word: &'a str,
+    query: &'a SearchQuery,
     selected_match: Option<Location>,
     highlights: HashMap<LineIdx, Vec<Annotation>>,
 }
 
 impl<'a> SearchResultHighlighter<'a> {
-    pub fn new(matched_word: &'a str, selected_match: Option<Location>) -> Self {
+    pub fn new(query: &'a SearchQuery, selected_match: Option<Location>) -> Self {
         Self {
-            matched_word,
+            query,
             selected_match,
             highlights: HashMap::new(),
         }
     }
 
-    fn highlight_matched_words(&self, line: &Line, result: &mut Vec<Annotation>) {  
-            if self.matched_word.is_empty() {
+    // Only looks for matches around the visible bytes of the line, a long line may hold far more matches than fit on screen
+    pub fn highlight_visible(&mut self, idx: LineIdx, line: &Line, visible: Range<ByteIdx>) {
+        let mut result = Vec::new();
+        self.highlight_matches(idx, line, visible, &mut result);
+        self.highlights.insert(idx, result);
+    }
+
+    fn highlight_matches(&self, idx: LineIdx, line: &Line, visible: Range<ByteIdx>, result: &mut Vec<Annotation>) {  
+            if self.query.is_empty() {
                 return;
             }
-            
-            line.find_all(self.matched_word, 0..line.len())
-                .iter()
-                .for_each(|(start, _)| {
+            let selected_byte_idx = self
+                .selected_match
+                .filter(|selected_match| selected_match.line_idx == idx && selected_match.grapheme_idx <= line.grapheme_count())
+                .map(|selected_match| line.grapheme_idx_to_byte_idx(selected_match.grapheme_idx));
+
+            //matches starting a little left of the visible part may still reach into it
+            let start = line.floor_char_boundary(visible.start.saturating_sub(MATCH_LOOKBEHIND));
+            line.find_matches(self.query, start..line.len())
+                .take_while(|byte_range| byte_range.start < visible.end)
+                .filter(|byte_range| byte_range.end > visible.start)
+                .for_each(|byte_range| {
+                    //the match starting at the selected location is the selected one, matches are highlighted along their actual length
+                    let annotation_type = if selected_byte_idx == Some(byte_range.start) {
+                        AnnotationType::SelectedMatch
+                    } else {
+                        AnnotationType::Match
+                    };
                     result.push(Annotation {
-                        annotation_type: AnnotationType::Match,
-                        start: *start,
-                        end: start.saturating_add(self.matched_word.len()),
+                        annotation_type,
+                        start: byte_range.start,
+                        end: byte_range.end,
                     });
                 });
         }
-        fn highlight_selected_match(&self,result:&mut Vec<Annotation>){
-            if let Some(selected_match) = self.selected_match{
-                if self.matched_word.is_empty(){
-                    return;
-                }
-                let start = selected_match.grapheme_idx;
-                result.push(Annotation {
-                annotation_type: AnnotationType::SelectedMatch,
-                start,
-                end: start.saturating_add(self.matched_word.len()),
-            });
-            }
-            
-        }
-        
     }
 
-    impl<'a>SyntaxHighlighter for SearchResultHighlighter<'a>{
+    impl SyntaxHighlighter for SearchResultHighlighter<'_>{
         fn highlight(&mut self, idx: LineIdx, line: &Line) {
-            let mut result = Vec::new();
-            self.highlight_matched_words(line, &mut result);
-            if let Some(selected_match) = self.selected_match{
-                if selected_match.line_idx == idx{
-                    self.highlight_selected_match(&mut result);
-                }
-            }
-            self.highlights.insert(idx,result);
+            self.highlight_visible(idx, line, 0..line.len());
         }
 
         fn get_annotations(&self, idx:LineIdx)->Option<&Vec<Annotation>> {
             self.highlights.get(&idx)
         }
-    }
\ No newline at end of file
+    }
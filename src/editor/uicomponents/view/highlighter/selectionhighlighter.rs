@@ -0,0 +1,53 @@
+//Annotate the part of each line which is covered by the selection
+use std::{collections::HashMap, ops::Range};
+
+use super::{syntaxhighlighter::SyntaxHighlighter, Annotation, AnnotationType, Line};
+use crate::prelude::*;
+
+pub struct SelectionHighlighter {
+    selection: Range<Location>,
+    highlights: HashMap<LineIdx, Vec<Annotation>>,
+}
+
+impl SelectionHighlighter {
+    pub fn new(selection: Range<Location>) -> Self {
+        Self {
+            selection,
+            highlights: HashMap::new(),
+        }
+    }
+}
+
+impl SyntaxHighlighter for SelectionHighlighter {
+    fn highlight(&mut self, idx: LineIdx, line: &Line) {
+        let Range { start, end } = self.selection;
+        if idx < start.line_idx || idx > end.line_idx {
+            return;
+        }
+        //only first and last line are partially selected, all lines in between are selected as a whole
+        let from = if idx == start.line_idx {
+            line.grapheme_idx_to_byte_idx(start.grapheme_idx)
+        } else {
+            0
+        };
+        let to = if idx == end.line_idx {
+            line.grapheme_idx_to_byte_idx(end.grapheme_idx)
+        } else {
+            line.len()
+        };
+        if from < to {
+            self.highlights.insert(
+                idx,
+                vec![Annotation {
+                    annotation_type: AnnotationType::Selection,
+                    start: from,
+                    end: to,
+                }],
+            );
+        }
+    }
+
+    fn get_annotations(&self, idx: LineIdx) -> Option<&Vec<Annotation>> {
+        self.highlights.get(&idx)
+    }
+}
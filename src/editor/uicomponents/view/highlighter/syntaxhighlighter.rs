@@ -5,5 +5,13 @@ use crate::prelude::*;
 pub trait SyntaxHighlighter{
     fn highlight(&mut self, idx: LineIdx, line: &Line);
     fn get_annotations(&self, idx:LineIdx)->Option<&Vec<Annotation>>;
+
+    // Number of lines from the start of the document whose annotations are kept up to date across frames.
+    // Highlighters which only live for one frame keep none.
+    fn highlighted_lines(&self) -> LineIdx {
+        0
+    }
+    // Forgets the annotations from the given line on, because it was edited
+    fn invalidate_from(&mut self, _idx: LineIdx) {}
 }
 //trait to define how syntax highlighter should look like
\ No newline at end of file
@@ -0,0 +1,22 @@
+use crate::prelude::*;
+use super::super::super::super::LineEnding;
+
+// A single modification of the buffer. Lines within `text` are separated by '\n'.
+// `from` and `to` span the text as it is present in the buffer: after applying an Insert, before applying a Remove.
+#[derive(Clone)]
+pub enum Change {
+    Insert {
+        from: Location,
+        to: Location,
+        text: String,
+    },
+    Remove {
+        from: Location,
+        to: Location,
+        text: String,
+    },
+    SetLineEnding {
+        from: LineEnding,
+        to: LineEnding,
+    },
+}
@@ -0,0 +1,80 @@
+use crate::prelude::*;
+
+mod change;
+pub use change::Change;
+mod step;
+pub use step::Step;
+
+pub struct History {
+    undo_stack: Vec<Step>,
+    redo_stack: Vec<Step>,
+    pending: Vec<Change>, // changes recorded by the buffer which are not yet part of a step
+    saved_at: Option<usize>, // depth of the undo stack at the last save, None if that state can no longer be reached
+}
+
+impl Default for History {
+    fn default() -> Self {
+        Self {
+            undo_stack: Vec::new(),
+            redo_stack: Vec::new(),
+            pending: Vec::new(),
+            saved_at: Some(0), // a freshly loaded or created buffer is in its saved state
+        }
+    }
+}
+
+impl History {
+    pub fn record(&mut self, change: Change) {
+        self.pending.push(change);
+    }
+
+    // Turns all pending changes into one undo step. If possible, the step is merged into the previous one,
+    // so that a run of typed characters can be undone at once.
+    pub fn commit(&mut self, caret_before: Location, caret_after: Location, mergeable: bool) {
+        if self.pending.is_empty() {
+            return;
+        }
+        let changes = std::mem::take(&mut self.pending);
+        self.redo_stack.clear();
+        if self.saved_at.is_some_and(|saved_at| saved_at > self.undo_stack.len()) {
+            self.saved_at = None; // saved state was only reachable through redo
+        }
+        let at_saved_state = self.is_at_saved_state();
+        if let Some(last) = self.undo_stack.last_mut()
+            && mergeable
+            && last.mergeable
+            && last.caret_after == caret_before
+            && !at_saved_state
+        {
+            last.changes.extend(changes);
+            last.caret_after = caret_after;
+            return;
+        }
+        self.undo_stack.push(Step {
+            changes,
+            caret_before,
+            caret_after,
+            mergeable,
+        });
+    }
+
+    pub fn undo(&mut self) -> Option<Step> {
+        let step = self.undo_stack.pop()?;
+        self.redo_stack.push(step.clone());
+        Some(step)
+    }
+
+    pub fn redo(&mut self) -> Option<Step> {
+        let step = self.redo_stack.pop()?;
+        self.undo_stack.push(step.clone());
+        Some(step)
+    }
+
+    pub fn mark_saved(&mut self) {
+        self.saved_at = Some(self.undo_stack.len());
+    }
+
+    pub fn is_at_saved_state(&self) -> bool {
+        self.saved_at == Some(self.undo_stack.len())
+    }
+}
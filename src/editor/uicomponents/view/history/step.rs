@@ -0,0 +1,12 @@
+use crate::prelude::*;
+use super::Change;
+
+// One undo step: all changes produced by a single edit command (or a run of typed characters),
+// together with the caret location before and after the edit.
+#[derive(Clone)]
+pub struct Step {
+    pub changes: Vec<Change>,
+    pub caret_before: Location,
+    pub caret_after: Location,
+    pub mergeable: bool, // consecutive character inserts are merged into one step
+}
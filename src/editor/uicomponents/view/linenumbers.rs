@@ -0,0 +1,30 @@
+use std::fmt::{self, Display};
+
+#[derive(Default, Clone, Copy, PartialEq, Eq)]
+pub enum LineNumbers {
+    #[default]
+    Off,
+    Absolute,
+    Relative, // distance to the current line, the current line itself keeps its absolute number
+}
+
+impl LineNumbers {
+    pub const fn next(self) -> Self {
+        match self {
+            Self::Off => Self::Absolute,
+            Self::Absolute => Self::Relative,
+            Self::Relative => Self::Off,
+        }
+    }
+}
+
+impl Display for LineNumbers {
+    fn fmt(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            Self::Off => "off",
+            Self::Absolute => "absolute",
+            Self::Relative => "relative",
+        };
+        write!(formatter, "{name}")
+    }
+}
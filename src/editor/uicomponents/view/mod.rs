@@ -1,9 +1,17 @@
 use super::super::{
     command::{Edit, Move},
-    DocumentStatus, Line, Terminal,
+    AnnotationType, Config, DocumentStatus, Line, LineEnding, SearchOption, SearchOptions, SearchQuery, Terminal,
 };
 use super::UIComponent;
-use std::{cmp::min, io::Error};
+use std::{
+    cmp::{max, min},
+    cell::RefCell,
+    io::Error,
+    ops::Range,
+    path::PathBuf,
+    rc::Rc,
+    time::Instant,
+};
 use crate::editor::RowIdx;
 use crate::prelude::*;
 
@@ -16,44 +24,99 @@ use highlighter::Highlighter;
 
 mod fileinfo;
 use fileinfo::FileInfo;
+mod atomicwrite;
+mod filestamp;
+use filestamp::FileStamp;
+mod history;
 mod searchinfo;
 use searchinfo::SearchInfo;
+mod replaceinfo;
+use replaceinfo::ReplaceInfo;
+mod linenumbers;
+use linenumbers::LineNumbers;
+mod visualrow;
+use visualrow::VisualRow;
 
 #[derive(Default)]
 pub struct View {
-    buffer: Buffer,
+    buffer: Rc<RefCell<Buffer>>, // shared with the other panes showing the same file
     needs_redraw: bool,
     // The view always starts at `(0/0)`. The `size` property determines the visible area.
     size: Size,
     text_location: Location,
+    selection_anchor: Option<Location>, // the selection spans from the anchor to the text location
     scroll_offset: Position,
     search_info: Option<SearchInfo>,
+    search_options: SearchOptions, // kept across searches
+    replace_info: Option<ReplaceInfo>,
+    line_numbers: LineNumbers,
+    soft_wrap: bool, // if set, scroll_offset.col stays 0 and the view starts at top_wrapped_row of the line scroll_offset.row
+    top_wrapped_row: RowIdx, // the first of the wrapped rows of the top line which is shown, 0 without soft wrap
 }
 
 impl View {
     //no need of new method as editor can create it from outside and configure as needed
 
     pub fn get_status(&self) -> DocumentStatus {
-        let file_info = self.buffer.get_file_info();
+        let buffer = self.buffer.borrow();
+        let file_info = buffer.get_file_info();
         DocumentStatus {
-            total_lines: self.buffer.height(),
+            total_lines: buffer.height(),
             current_line_idx: self.text_location.line_idx,
             file_name: format!("{file_info}"), // use of debug trait for file info
-            is_modified: self.buffer.is_dirty(),//Updates revolve around buffer grants no pub access to some fields 
+            is_modified: buffer.is_dirty(),//Updates revolve around buffer grants no pub access to some fields 
             file_type: file_info.get_file_type(),//propagate file_type around Document_Statues
+            line_ending: file_info.get_line_ending(),
         }
     }
 
-    pub const fn is_file_loaded(&self) -> bool {
-        self.buffer.is_file_loaded()
+    pub fn get_file_path(&self) -> Option<PathBuf> {
+        self.buffer.borrow().get_file_info().get_path().map(PathBuf::from)
+    }
+
+    pub fn is_file_loaded(&self) -> bool {
+        self.buffer.borrow().is_file_loaded()
     } // allows editor to determine whether or not to prompt for file_name
 
+    // Creates a second view onto the same buffer, e.g. for a new pane. Edits made in either view show up in both.
+    pub fn split(&self) -> Self {
+        Self {
+            buffer: Rc::clone(&self.buffer),
+            needs_redraw: true,
+            size: self.size,
+            text_location: self.text_location,
+            selection_anchor: None,
+            scroll_offset: self.scroll_offset,
+            search_info: None,
+            search_options: self.search_options,
+            replace_info: None,
+            line_numbers: self.line_numbers,
+            soft_wrap: self.soft_wrap,
+            top_wrapped_row: self.top_wrapped_row,
+        }
+    }
+
+    pub fn shows_same_buffer(&self, other: &Self) -> bool {
+        Rc::ptr_eq(&self.buffer, &other.buffer)
+    }
+
+    // Brings the caret back into the document after another view onto the same buffer changed it.
+    pub fn snap_to_buffer(&mut self) {
+        self.clear_selection();
+        self.snap_to_valid_line();
+        self.snap_to_valid_grapheme();
+        self.scroll_text_location_into_view();
+        self.set_needs_redraw(true);
+    }
+
     //region: Search
     pub fn enter_search(&mut self) {
+        self.clear_selection();
         //entering means storing prev location
         self.search_info = Some(SearchInfo {
             prev_location: self.text_location,
             prev_scroll_offset: self.scroll_offset,
+            prev_top_wrapped_row: self.top_wrapped_row,
             query: None, //made query optional since no compulsion on query to be present while search active now
         });
     }
@@ -69,6 +132,7 @@ impl View {
         if let Some(search_info) = &self.search_info {
             self.text_location = search_info.prev_location;
             self.scroll_offset = search_info.prev_scroll_offset;
+            self.top_wrapped_row = search_info.prev_top_wrapped_row;
             self.scroll_text_location_into_view(); // ensure prev location still visible even if terminal resize during search
                                                    /*
                                                    Suppose you have a wide terminal. You store the text location and scroll offset and enter search. You resize the screen and dismiss search.
@@ -78,18 +142,30 @@ impl View {
         self.exit_search();
     }
 
-    pub fn search(&mut self, query: &str) {
+    // Searches for query, interpreted according to the current search options.
+    // An invalid query is rejected, keeping the previous query and its highlighted results.
+    pub fn search(&mut self, query: &str) -> Result<(), regex::Error> {
+        let query = SearchQuery::new(query, self.search_options)?;
         if let Some(search_info) = &mut self.search_info {
-            search_info.query = Some(Line::from(query));
+            search_info.query = Some(query);
         }
         self.search_in_direction(self.text_location, SearchDirection::default());
         //calls new method and searches in default direction i.e forward
+        Ok(())
+    }
+
+    pub const fn get_search_options(&self) -> SearchOptions {
+        self.search_options
+    }
+
+    pub const fn toggle_search_option(&mut self, option: SearchOption) {
+        self.search_options.toggle(option);
     }
 
     // Attempts to get the current search query - for scenarios where the search query absolutely must be there.
     // Panics if not present in debug, or if search info is not present in debug
     // Returns None on release.
-    fn get_search_query(&self) -> Option<&Line> {
+    fn get_search_query(&self) -> Option<&SearchQuery> {
         //showcase how to retrive a double option search_info is optiona and query is also a option
 
         let query = self
@@ -106,26 +182,26 @@ impl View {
 
     fn search_in_direction(&mut self, from: Location, direction: SearchDirection) {
         //renamed function
-        if let Some(location) = self.get_search_query().and_then(|query| {
+        if let Some(found) = self.get_search_query().and_then(|query| {
             //get location of next match by getting query
             if query.is_empty() {
                 None
             } else if direction == SearchDirection::Forward {
-                self.buffer.search_forward(query, from)
+                self.buffer.borrow().search_forward(query, from)
             } else {
-                self.buffer.search_backward(query, from)
+                self.buffer.borrow().search_backward(query, from)
             } //calling the specialised search fxn
         }) {
-            self.text_location = location;
+            self.text_location = found.start;
             self.center_text_location(); //handling the result as before
-        };
+        }
         self.set_needs_redraw(true); //to make highlighting show up we trigger redraw upon search
     }
 
     pub fn search_next(&mut self) {
         let step_right = self
             .get_search_query()
-            .map_or(1, |query| min(query.grapheme_count(), 1));
+            .map_or(1, |query| usize::from(!query.is_empty()));
 
         let location = Location {
             line_idx: self.text_location.line_idx,
@@ -142,23 +218,497 @@ impl View {
 
     //end region
 
+    //region: Replace
+    // Starts replacing the current search query, beginning with the next match at or after the caret.
+    // Returns false if there is nothing to replace.
+    pub fn enter_replace(&mut self, replacement: &str) -> bool {
+        self.replace_info = Some(ReplaceInfo {
+            replacement: Line::from(replacement),
+            start: self.text_location,
+            wrapped: false,
+            replacements: 0,
+        });
+        let found = self.find_replace_candidate(self.text_location);
+        if let Some(replace_info) = &mut self.replace_info {
+            replace_info.start = self.text_location;
+            replace_info.wrapped = false; //finding the first candidate may wrap, that doesn't count
+        }
+        found
+    }
+
+    // Exits replacing as well as the underlying search and returns the number of replacements made.
+    pub fn exit_replace(&mut self) -> usize {
+        let replacements = self
+            .replace_info
+            .take()
+            .map_or(0, |replace_info| replace_info.replacements);
+        self.exit_search();
+        replacements
+    }
+
+    // Replaces the current candidate and moves on to the next one. Returns false if there is none left.
+    pub fn replace_current(&mut self) -> bool {
+        let caret_before = self.text_location;
+        let next = self.replace_candidate();
+        self.buffer.borrow_mut()
+            .commit_edit(caret_before, self.text_location, false);
+        self.find_replace_candidate(next)
+    }
+
+    // Moves on to the next candidate without replacing. Returns false if there is none left.
+    pub fn skip_current(&mut self) -> bool {
+        let next = Location {
+            line_idx: self.text_location.line_idx,
+            grapheme_idx: self.text_location.grapheme_idx.saturating_add(1),
+        };
+        self.find_replace_candidate(next)
+    }
+
+    // Replaces the current and all remaining candidates as one undo step.
+    pub fn replace_all(&mut self) {
+        let caret_before = self.text_location;
+        loop {
+            let next = self.replace_candidate();
+            if !self.find_replace_candidate(next) {
+                break;
+            }
+        }
+        self.buffer.borrow_mut()
+            .commit_edit(caret_before, self.text_location, false);
+    }
+
+    // Replaces the match at the caret and returns the location right behind the replacement.
+    fn replace_candidate(&mut self) -> Location {
+        let at = self.text_location;
+        let (Some(query), Some(replace_info)) = (
+            self.search_info
+                .as_ref()
+                .and_then(|search_info| search_info.query.as_ref()),
+            &mut self.replace_info,
+        ) else {
+            return at;
+        };
+        let Some(found) = self
+            .buffer.borrow()
+            .search_forward(query, at)
+            .filter(|found| found.start == at)
+        else {
+            return at; // caret is not on a match, nothing to replace
+        };
+        let query_len = found.end.grapheme_idx.saturating_sub(at.grapheme_idx);
+        let replacement_len = replace_info.replacement.grapheme_count();
+        self.buffer.borrow_mut().remove_text(found.start, found.end);
+        self.text_location = self
+            .buffer.borrow_mut()
+            .insert_text(&replace_info.replacement, at);
+        replace_info.replacements = replace_info.replacements.saturating_add(1);
+        if replace_info.wrapped
+            && at.line_idx == replace_info.start.line_idx
+            && at < replace_info.start
+        {
+            //keep the first candidate in place when replacing in front of it on the same line
+            replace_info.start.grapheme_idx = replace_info
+                .start
+                .grapheme_idx
+                .saturating_add(replacement_len)
+                .saturating_sub(query_len);
+        }
+        self.set_needs_redraw(true);
+        self.text_location
+    }
+
+    // Moves the caret to the next match at or after from, unless the search has wrapped around to the first candidate.
+    fn find_replace_candidate(&mut self, from: Location) -> bool {
+        let (Some(query), Some(replace_info)) = (
+            self.search_info
+                .as_ref()
+                .and_then(|search_info| search_info.query.as_ref()),
+            &mut self.replace_info,
+        ) else {
+            return false;
+        };
+        let Some(location) = self
+            .buffer.borrow()
+            .search_forward(query, from)
+            .map(|found| found.start)
+        else {
+            return false;
+        };
+        if location < from {
+            replace_info.wrapped = true;
+        }
+        if replace_info.wrapped && location >= replace_info.start {
+            return false;
+        }
+        self.text_location = location;
+        self.center_text_location();
+        true
+    }
+    //end region
+
+    // Converts the document between LF and CRLF line endings, mixed documents become LF. Returns the new line ending.
+    pub fn convert_line_endings(&mut self) -> LineEnding {
+        let line_ending = self.buffer.borrow().get_file_info().get_line_ending().converted();
+        let caret_before = self.text_location;
+        self.buffer.borrow_mut().set_line_ending(line_ending);
+        self.snap_to_valid_grapheme(); //the caret may have been behind a removed carriage return
+        self.buffer.borrow_mut()
+            .commit_edit(caret_before, self.text_location, false);
+        self.set_needs_redraw(true);
+        line_ending
+    }
+
+    //region: Line numbers
+    pub fn cycle_line_numbers(&mut self) -> LineNumbers {
+        self.line_numbers = self.line_numbers.next();
+        //the gutter changes the width available for text
+        self.scroll_text_location_into_view();
+        self.set_needs_redraw(true);
+        self.line_numbers
+    }
+
+    // Width of the gutter including the separating space, 0 if line numbers are off or would not leave room for text.
+    fn gutter_width(&self) -> usize {
+        if self.line_numbers == LineNumbers::Off {
+            return 0;
+        }
+        let digits = max(self.buffer.borrow().height(), 1).to_string().len();
+        let gutter_width = digits.saturating_add(1);
+        if gutter_width >= self.size.width {
+            0
+        } else {
+            gutter_width
+        }
+    }
+
+    fn text_width(&self) -> usize {
+        self.size.width.saturating_sub(self.gutter_width())
+    }
+
+    fn build_gutter(&self, visual_row: &VisualRow, gutter_width: usize) -> String {
+        if visual_row.is_continuation {
+            return " ".repeat(gutter_width);
+        }
+        let line_idx = visual_row.line_idx;
+        let current_line_idx = self.text_location.line_idx;
+        let number = if self.line_numbers == LineNumbers::Relative && line_idx != current_line_idx {
+            line_idx.abs_diff(current_line_idx)
+        } else {
+            line_idx.saturating_add(1)
+        };
+        let digits = gutter_width.saturating_sub(1);
+        format!("{number:>digits$} ")
+    }
+    //end region
+
+    //region: Soft wrap
+    pub fn toggle_soft_wrap(&mut self) -> bool {
+        self.soft_wrap = !self.soft_wrap;
+        self.scroll_offset.col = 0;
+        self.top_wrapped_row = 0; //the top line stays on top
+        self.scroll_text_location_into_view();
+        self.set_needs_redraw(true);
+        self.soft_wrap
+    }
+
+    fn wrap(&self, line_idx: LineIdx) -> Vec<Range<GraphemeIdx>> {
+        self.buffer.borrow().wrap(line_idx, self.text_width())
+    }
+
+    // Returns the index of the wrapped row containing the grapheme. A grapheme index at the boundary of two rows belongs to the lower one.
+    fn wrapped_row_idx(rows: &[Range<GraphemeIdx>], grapheme_idx: GraphemeIdx) -> usize {
+        rows.iter()
+            .position(|row| grapheme_idx < row.end)
+            .unwrap_or(rows.len().saturating_sub(1))
+    }
+
+    // The line and wrapped row the caret is on, along with its column within that row
+    fn wrapped_caret(&self) -> ((LineIdx, RowIdx), ColIdx) {
+        let Location {
+            line_idx,
+            grapheme_idx,
+        } = self.text_location;
+        let rows = self.wrap(line_idx);
+        let row_idx = Self::wrapped_row_idx(&rows, grapheme_idx);
+        let buffer = self.buffer.borrow();
+        let col = buffer
+            .width_until(line_idx, grapheme_idx)
+            .saturating_sub(buffer.width_until(line_idx, rows[row_idx].start));
+        ((line_idx, row_idx), col)
+    }
+
+    // Number of screen rows from the wrapped row from down to the wrapped row to, 0 if to lies above from.
+    // Only the lines in between are wrapped, and counting stops at limit.
+    fn wrapped_rows_between(&self, from: (LineIdx, RowIdx), to: (LineIdx, RowIdx), limit: RowIdx) -> RowIdx {
+        if to <= from {
+            return 0;
+        }
+        let (mut line_idx, mut row_idx) = from;
+        let mut rows: RowIdx = 0;
+        while line_idx < to.0 {
+            rows = rows.saturating_add(self.wrap(line_idx).len().saturating_sub(row_idx));
+            if rows >= limit {
+                return limit;
+            }
+            line_idx = line_idx.saturating_add(1);
+            row_idx = 0;
+        }
+        min(rows.saturating_add(to.1.saturating_sub(row_idx)), limit)
+    }
+
+    // The wrapped row the given number of screen rows above the wrapped row from, or the first row of the document
+    fn wrapped_row_above(&self, from: (LineIdx, RowIdx), count: RowIdx) -> (LineIdx, RowIdx) {
+        let (mut line_idx, mut row_idx) = from;
+        let mut remaining = count;
+        while remaining > row_idx {
+            if line_idx == 0 {
+                return (0, 0);
+            }
+            remaining = remaining.saturating_sub(row_idx.saturating_add(1));
+            line_idx = line_idx.saturating_sub(1);
+            row_idx = self.wrap(line_idx).len().saturating_sub(1);
+        }
+        (line_idx, row_idx.saturating_sub(remaining))
+    }
+
+    // The line and wrapped row shown at the top of the view
+    const fn wrapped_top(&self) -> (LineIdx, RowIdx) {
+        (self.scroll_offset.row, self.top_wrapped_row)
+    }
+
+    fn set_wrapped_top(&mut self, (line_idx, row_idx): (LineIdx, RowIdx)) {
+        if self.wrapped_top() != (line_idx, row_idx) {
+            self.scroll_offset.row = line_idx;
+            self.top_wrapped_row = row_idx;
+            self.set_needs_redraw(true);
+        }
+    }
+
+    // Moves the caret by the given number of screen rows, keeping its column. Returns early at the start or end of the document.
+    fn move_vertically_wrapped(&mut self, step: usize, down: bool) {
+        let Location {
+            mut line_idx,
+            grapheme_idx,
+        } = self.text_location;
+        let mut rows = self.wrap(line_idx);
+        let mut row_idx = Self::wrapped_row_idx(&rows, grapheme_idx);
+        let col = self
+            .buffer.borrow()
+            .width_until(line_idx, grapheme_idx)
+            .saturating_sub(self.buffer.borrow().width_until(line_idx, rows[row_idx].start));
+        for _ in 0..step {
+            if down {
+                if row_idx.saturating_add(1) < rows.len() {
+                    row_idx = row_idx.saturating_add(1);
+                } else if line_idx < self.buffer.borrow().height() {
+                    line_idx = line_idx.saturating_add(1);
+                    rows = self.wrap(line_idx);
+                    row_idx = 0;
+                } else {
+                    break;
+                }
+            } else if row_idx > 0 {
+                row_idx = row_idx.saturating_sub(1);
+            } else if line_idx > 0 {
+                line_idx = line_idx.saturating_sub(1);
+                rows = self.wrap(line_idx);
+                row_idx = rows.len().saturating_sub(1);
+            } else {
+                break;
+            }
+        }
+        let row = rows[row_idx].clone();
+        let is_last_row = row_idx.saturating_add(1) == rows.len();
+        let mut grapheme_idx = self.buffer.borrow().grapheme_idx_at(line_idx, row.clone(), col);
+        if !is_last_row && grapheme_idx == row.end {
+            //the end of a row is the start of the next one, stay on the last grapheme instead
+            grapheme_idx = row.end.saturating_sub(1);
+        }
+        self.text_location = Location {
+            grapheme_idx,
+            line_idx,
+        };
+    }
+
+    // Determines which part of which line is shown on each row of the view, starting at the top. Rows below the document are omitted.
+    fn visible_rows(&self) -> Vec<VisualRow> {
+        let Size { height, .. } = self.size;
+        let text_width = self.text_width();
+        if !self.soft_wrap {
+            let left = self.scroll_offset.col;
+            return (self.scroll_offset.row..self.scroll_offset.row.saturating_add(height))
+                .take_while(|&line_idx| line_idx < self.buffer.borrow().height())
+                .map(|line_idx| VisualRow {
+                    line_idx,
+                    columns: left..left.saturating_add(text_width),
+                    is_continuation: false,
+                })
+                .collect();
+        }
+        let mut visible_rows = Vec::with_capacity(height);
+        let (top_line_idx, mut rows_to_skip) = self.wrapped_top();
+        for line_idx in top_line_idx..self.buffer.borrow().height() {
+            let rows = self.wrap(line_idx);
+            for (row_idx, row) in rows.into_iter().enumerate().skip(rows_to_skip) {
+                if visible_rows.len() == height {
+                    return visible_rows;
+                }
+                visible_rows.push(VisualRow {
+                    line_idx,
+                    columns: self.buffer.borrow().width_until(line_idx, row.start)
+                        ..self.buffer.borrow().width_until(line_idx, row.end),
+                    is_continuation: row_idx > 0,
+                });
+            }
+            rows_to_skip = 0;
+        }
+        visible_rows
+    }
+    //end region
+
+    //region: Go to line
+    // Moves the caret to the target and centers it. Accepted targets (lines and columns are 1-based):
+    // `line`, `line:column`, `+lines` / `-lines` relative to the current line and `%percent` of the document.
+    pub fn goto(&mut self, target: &str) -> Result<(), String> {
+        let (line_part, column_part) = target
+            .split_once(':')
+            .map_or((target, None), |(line, column)| (line, Some(column)));
+        let line_idx = self.parse_goto_line(line_part.trim())?;
+        let grapheme_count = self.buffer.borrow().grapheme_count(line_idx);
+        let grapheme_idx = match column_part {
+            Some(column_part) => {
+                let column = Self::parse_goto_number(column_part.trim())?;
+                if column == 0 || column > grapheme_count.saturating_add(1) {
+                    return Err(format!(
+                        "Column {column} is out of range (1-{})",
+                        grapheme_count.saturating_add(1)
+                    ));
+                }
+                column.saturating_sub(1)
+            }
+            None => 0,
+        };
+        self.clear_selection();
+        self.text_location = Location {
+            grapheme_idx,
+            line_idx,
+        };
+        self.center_text_location();
+        Ok(())
+    }
+
+    fn parse_goto_line(&self, line_part: &str) -> Result<LineIdx, String> {
+        let line_count = max(self.buffer.borrow().height(), 1); //an empty document still has a first line to go to
+        let current_line_idx = self.text_location.line_idx;
+        let line_idx = if let Some(percent) = line_part.strip_prefix('%') {
+            let percent = Self::parse_goto_number(percent)?;
+            if percent > 100 {
+                return Err(format!("Percentage {percent} is out of range (0-100)"));
+            }
+            #[allow(clippy::integer_division)]
+            let line_idx = line_count.saturating_sub(1).saturating_mul(percent) / 100;
+            line_idx
+        } else if let Some(lines) = line_part.strip_prefix('+') {
+            current_line_idx.saturating_add(Self::parse_goto_number(lines)?)
+        } else if let Some(lines) = line_part.strip_prefix('-') {
+            current_line_idx
+                .checked_sub(Self::parse_goto_number(lines)?)
+                .ok_or_else(|| format!("Line {line_part} is out of range (1-{line_count})"))?
+        } else {
+            Self::parse_goto_number(line_part)?
+                .checked_sub(1)
+                .ok_or_else(|| format!("Line 0 is out of range (1-{line_count})"))?
+        };
+        if line_idx >= line_count {
+            return Err(format!(
+                "Line {} is out of range (1-{line_count})",
+                line_idx.saturating_add(1)
+            ));
+        }
+        Ok(line_idx)
+    }
+
+    fn parse_goto_number(value: &str) -> Result<usize, String> {
+        value
+            .parse()
+            .map_err(|_| format!("Invalid number: {value}"))
+    }
+    //end region
+
     //region:File io
     pub fn load(&mut self, file_name: &str) -> Result<(), Error> {
         let buffer = Buffer::load(file_name)?;
 
-        self.buffer = buffer;
+        self.buffer = Rc::new(RefCell::new(buffer));
+        self.set_needs_redraw(true);
+        Ok(())
+    }
+
+    // Loads the file again, e.g. after another process changed it. The caret stays in place as far as possible.
+    pub fn reload(&mut self) -> Result<(), Error> {
+        let Some(path) = self.get_file_path() else {
+            return Ok(());
+        };
+        let buffer = Buffer::load(&path.to_string_lossy())?;
+        *self.buffer.borrow_mut() = buffer; //replaced in place, so that other panes showing the file see the new contents
+        self.clear_selection();
+        self.snap_to_valid_line();
+        self.snap_to_valid_grapheme();
+        self.scroll_text_location_into_view();
+        self.set_needs_redraw(true);
+        Ok(())
+    }
+
+    pub fn is_changed_on_disk(&self) -> bool {
+        self.buffer.borrow().is_changed_on_disk()
+    }
+
+    pub fn ignore_disk_changes(&mut self) {
+        self.buffer.borrow_mut().ignore_disk_changes();
+    }
+
+    pub fn update_swap_file(&mut self, force: bool) -> Result<(), Error> {
+        self.buffer.borrow_mut().update_swap_file(force)
+    }
+
+    pub fn swap_file_due(&self) -> Option<Instant> {
+        self.buffer.borrow().swap_file_due()
+    }
+
+    pub fn remove_swap_file(&mut self) -> Result<(), Error> {
+        self.buffer.borrow_mut().remove_swap_file()
+    }
+
+    pub fn has_newer_swap_file(&self) -> bool {
+        self.buffer.borrow().has_newer_swap_file()
+    }
+
+    pub fn recover_swap_file(&mut self) -> Result<(), Error> {
+        self.buffer.borrow_mut().recover_swap_file()?;
+        self.clear_selection();
+        self.snap_to_valid_line();
+        self.snap_to_valid_grapheme();
+        self.scroll_text_location_into_view();
         self.set_needs_redraw(true);
         Ok(())
     }
 
+    pub fn discard_swap_file(&mut self) -> Result<(), Error> {
+        self.buffer.borrow_mut().discard_swap_file()
+    }
+
+    pub fn keep_swap_file(&mut self) {
+        self.buffer.borrow_mut().keep_swap_file();
+    }
+
     pub fn save(&mut self) -> Result<(), Error> {
-        self.buffer.save()?;
+        self.buffer.borrow_mut().save()?;
         self.set_needs_redraw(true);
         Ok(())// mark view for redraw to ensure highlighting before saving
     }
 
     pub fn save_as(&mut self, file_name: &str) -> Result<(), Error> {
-        self.buffer.save_as(file_name)?;
+        self.buffer.borrow_mut().save_as(file_name)?;
         self.set_needs_redraw(true);
         Ok(())// mark view for redraw to ensure highlighting before saving
     } //allows saving by file name
@@ -166,16 +716,62 @@ impl View {
     //end region
     // region: CommandHandling
     pub fn handle_edit_command(&mut self, command: Edit) {
+        let caret_before = self.text_location;
+        //typing and deleting replace the selection, if there is one
+        let replaced_selection = matches!(
+            command,
+            Edit::Insert(_)
+                | Edit::InsertText(_)
+                | Edit::InsertNewLine
+                | Edit::Delete
+                | Edit::DeleteBackward
+                | Edit::DeleteWord
+                | Edit::DeleteWordBackward
+        ) && self.delete_selection();
+        let mergeable = matches!(command, Edit::Insert(_)) && !replaced_selection;
         match command {
             Edit::Insert(character) => self.insert_char(character),
+            Edit::InsertText(text) => self.insert_text(&text),
+            Edit::Delete | Edit::DeleteBackward | Edit::DeleteWord | Edit::DeleteWordBackward
+                if replaced_selection => {}
             Edit::Delete => self.delete(),
             Edit::DeleteBackward => self.delete_backward(),
+            Edit::DeleteWord => self.delete_word(),
+            Edit::DeleteWordBackward => self.delete_word_backward(),
             Edit::InsertNewLine => self.insert_newline(),
+            Edit::Undo => self.undo(),
+            Edit::Redo => self.redo(),
+            Edit::Cut | Edit::Copy | Edit::Paste => {} // handled by the editor, which owns the clipboard
         }
+        //group all changes the command made into one undo step, typed characters may be merged with the previous step
+        self.buffer.borrow_mut()
+            .commit_edit(caret_before, self.text_location, mergeable);
     }
     pub fn handle_move_command(&mut self, command: Move) {
+        self.clear_selection();
+        self.move_text_location(command);
+    }
+
+    pub fn handle_select_command(&mut self, command: Move) {
+        if self.selection_anchor.is_none() {
+            self.selection_anchor = Some(self.text_location);
+        }
+        self.move_text_location(command);
+        self.set_needs_redraw(true);
+    }
+
+    fn move_text_location(&mut self, command: Move) {
         let Size { height, .. } = self.size;
+        let line_idx_before = self.text_location.line_idx;
         match command {
+            Move::Up | Move::Down | Move::PageUp | Move::PageDown if self.soft_wrap => {
+                let step = if matches!(command, Move::PageUp | Move::PageDown) {
+                    height.saturating_sub(1)
+                } else {
+                    1
+                };
+                self.move_vertically_wrapped(step, matches!(command, Move::Down | Move::PageDown));
+            }
             Move::Up => self.move_up(1),
             Move::Down => self.move_down(1),
             Move::Left => self.move_left(),
@@ -184,15 +780,24 @@ impl View {
             Move::PageDown => self.move_down(height.saturating_sub(1)),
             Move::StartOfLine => self.move_to_start_of_line(),
             Move::EndOfLine => self.move_to_end_of_line(),
+            Move::WordLeft => self.move_word_left(),
+            Move::WordRight => self.move_word_right(),
+            Move::StartOfFile => self.move_to_start_of_file(),
+            Move::EndOfFile => self.move_to_end_of_file(),
+            Move::PreviousParagraph => self.move_to_previous_paragraph(),
+            Move::NextParagraph => self.move_to_next_paragraph(),
         }
         self.scroll_text_location_into_view();
+        if self.line_numbers != LineNumbers::Off && self.text_location.line_idx != line_idx_before {
+            self.set_needs_redraw(true); //the gutter emphasizes the current line and relative numbers depend on it
+        }
     }
 
     //endregion
 
     //region : Text editing\
     fn insert_newline(&mut self) {
-        self.buffer.insert_newline(self.text_location);
+        self.buffer.borrow_mut().insert_newline(self.text_location);
         self.handle_move_command(Move::Right);
         self.set_needs_redraw(true);
     }
@@ -204,16 +809,102 @@ impl View {
         }
     }
     fn delete(&mut self) {
-        self.buffer.delete(self.text_location);
+        self.buffer.borrow_mut().delete(self.text_location);
+        self.set_needs_redraw(true);
+    }
+    fn delete_word_backward(&mut self) {
+        let end = self.text_location;
+        self.move_word_left();
+        self.buffer.borrow_mut().remove_text(self.text_location, end);
+        self.scroll_text_location_into_view();
+        self.set_needs_redraw(true);
+    }
+    fn delete_word(&mut self) {
+        let start = self.text_location;
+        self.move_word_right();
+        let end = self.text_location;
+        self.text_location = start;
+        self.buffer.borrow_mut().remove_text(start, end);
+        self.set_needs_redraw(true);
+    }
+
+    //region: Selection
+    // Returns the selected range, ordered by appearance in the document. An empty selection counts as no selection.
+    fn selection(&self) -> Option<Range<Location>> {
+        let anchor = self.selection_anchor?;
+        (anchor != self.text_location)
+            .then(|| min(anchor, self.text_location)..max(anchor, self.text_location))
+    }
+
+    fn clear_selection(&mut self) {
+        if self.selection_anchor.take().is_some() {
+            self.set_needs_redraw(true); //ensure the selection highlight disappears
+        }
+    }
+
+    // Removes the selected text and places the caret where the selection started. Returns false if nothing was selected.
+    fn delete_selection(&mut self) -> bool {
+        let Some(selection) = self.selection() else {
+            return false;
+        };
+        self.buffer.borrow_mut().remove_text(selection.start, selection.end);
+        self.text_location = selection.start;
+        self.clear_selection();
+        self.scroll_text_location_into_view();
+        true
+    }
+    //end region
+
+    //region: Clipboard
+    pub fn copy_selection(&self) -> Option<String> {
+        self.selection()
+            .map(|selection| self.buffer.borrow().get_text(selection.start, selection.end))
+    }
+
+    pub fn cut_selection(&mut self) -> Option<String> {
+        let caret_before = self.text_location;
+        let text = self.copy_selection()?;
+        self.delete_selection();
+        self.buffer.borrow_mut().commit_edit(caret_before, self.text_location, false);
+        Some(text)
+    }
+    //end region
+
+    fn undo(&mut self) {
+        self.clear_selection();
+        let location = self.buffer.borrow_mut().undo();
+        if let Some(location) = location {
+            self.text_location = location;
+            self.scroll_text_location_into_view();
+            self.set_needs_redraw(true);
+        }
+    }
+
+    fn redo(&mut self) {
+        self.clear_selection();
+        let location = self.buffer.borrow_mut().redo();
+        if let Some(location) = location {
+            self.text_location = location;
+            self.scroll_text_location_into_view();
+            self.set_needs_redraw(true);
+        }
+    }
+
+    // Inserts a block of text which may span multiple lines in one operation.
+    fn insert_text(&mut self, text: &str) {
+        //terminals usually send pasted line breaks as carriage returns
+        let text = text.replace("\r\n", "\n").replace('\r', "\n");
+        self.text_location = self.buffer.borrow_mut().insert_text(&text, self.text_location);
+        self.scroll_text_location_into_view();
         self.set_needs_redraw(true);
     }
 
     fn insert_char(&mut self, character: char) {
-         let old_len = self.buffer.grapheme_count(self.text_location.line_idx);
+         let old_len = self.buffer.borrow().grapheme_count(self.text_location.line_idx);
 
-        self.buffer.insert_char(character, self.text_location);
+        self.buffer.borrow_mut().insert_char(character, self.text_location);
 
-        let new_len = self.buffer.grapheme_count(self.text_location.line_idx);
+        let new_len = self.buffer.borrow().grapheme_count(self.text_location.line_idx);
 
         let grapheme_delta = new_len.saturating_sub(old_len);
         if grapheme_delta > 0 {
@@ -228,8 +919,8 @@ impl View {
 
     // region: Rendering
 
-    fn render_line(at: RowIdx, line_text: &str) -> Result<(), Error> {
-        Terminal::print_row(at, line_text)
+    fn render_line(&self, at: Position, line_text: &str) {
+        Terminal::print_row(at, self.size.width, line_text);
     }
     fn build_welcome_message(width: usize) -> String {
         if width == 0 {
@@ -271,7 +962,10 @@ impl View {
     }
 
     fn scroll_horizontally(&mut self, to: ColIdx) {
-        let Size { width, .. } = self.size;
+        if self.soft_wrap {
+            return; //wrapped lines always fit horizontally
+        }
+        let width = self.text_width();
         let offset_changed = if to < self.scroll_offset.col {
             self.scroll_offset.col = to;
             true
@@ -287,14 +981,38 @@ impl View {
     }
 
     fn scroll_text_location_into_view(&mut self) {
+        if self.soft_wrap {
+            self.scroll_wrapped_caret_into_view();
+            return;
+        }
         let Position { row, col } = self.text_location_to_position();
         self.scroll_vertically(row);
         self.scroll_horizontally(col);
     }
+
+    // Scrolls by as few rows as possible, only wrapping the lines between the top of the view and the caret
+    fn scroll_wrapped_caret_into_view(&mut self) {
+        let Size { height, .. } = self.size;
+        let (caret, _) = self.wrapped_caret();
+        let top = self.wrapped_top();
+        if caret < top {
+            self.set_wrapped_top(caret);
+        } else if self.wrapped_rows_between(top, caret, height) >= height {
+            self.set_wrapped_top(self.wrapped_row_above(caret, height.saturating_sub(1)));
+        }
+    }
+
     fn center_text_location(&mut self) {
-        let Size { height, width } = self.size;
-        let Position { row, col } = self.text_location_to_position();
+        let Size { height, .. } = self.size;
         let vertical_mid = height.div_ceil(2);
+        if self.soft_wrap {
+            let (caret, _) = self.wrapped_caret();
+            self.set_wrapped_top(self.wrapped_row_above(caret, vertical_mid));
+            self.set_needs_redraw(true);
+            return;
+        }
+        let width = self.text_width();
+        let Position { row, col } = self.text_location_to_position();
         let horizontal_mid = width.div_ceil(2);
         self.scroll_offset.row = row.saturating_sub(vertical_mid);
         self.scroll_offset.col = col.saturating_sub(horizontal_mid);
@@ -304,15 +1022,26 @@ impl View {
     // region: Location and position handling
 
     pub fn caret_position(&self) -> Position {
-         self.text_location_to_position()
-            .saturating_sub(self.scroll_offset)
+        let Position { row, col } = if self.soft_wrap {
+            let (caret, col) = self.wrapped_caret();
+            let row = self.wrapped_rows_between(self.wrapped_top(), caret, self.size.height);
+            Position { col, row }
+        } else {
+            self.text_location_to_position()
+                .saturating_sub(self.scroll_offset)
+        };
+        Position {
+            row,
+            col: col.saturating_add(self.gutter_width()), //the caret sits right of the gutter
+        }
     }
 
-    pub fn text_location_to_position(&self) -> Position {
+    // The caret's line and column in the document, without soft wrap
+    fn text_location_to_position(&self) -> Position {
         let row = self.text_location.line_idx;
-        debug_assert!(row.saturating_sub(1) <= self.buffer.height());
+        debug_assert!(row.saturating_sub(1) <= self.buffer.borrow().height());
         let col = self
-            .buffer
+            .buffer.borrow()
             .width_until(row, self.text_location.grapheme_idx);
         Position { col, row }
     }
@@ -330,9 +1059,8 @@ impl View {
         self.snap_to_valid_line();
     }
     #[allow(clippy::arithmetic_side_effects)]
-
     fn move_right(&mut self) {
-       let grapheme_count = self.buffer.grapheme_count(self.text_location.line_idx);
+       let grapheme_count = self.buffer.borrow().grapheme_count(self.text_location.line_idx);
         if self.text_location.grapheme_idx < grapheme_count {
             self.text_location.grapheme_idx += 1;
         } else {
@@ -343,17 +1071,71 @@ impl View {
     #[allow(clippy::arithmetic_side_effects)]
     fn move_left(&mut self) {
         if self.text_location.grapheme_idx > 0 {
-            self.text_location.grapheme_idx = self.text_location.grapheme_idx - 1;
+            self.text_location.grapheme_idx -= 1;
         } else if self.text_location.line_idx > 0 {
             self.move_up(1);
             self.move_to_end_of_line();
         }
     }
+    // Word movement crosses line ends like move_left and move_right do
+    fn move_word_left(&mut self) {
+        if self.text_location.grapheme_idx == 0 {
+            self.move_left();
+        } else {
+            self.text_location.grapheme_idx = self.buffer.borrow().previous_word_start(self.text_location);
+        }
+    }
+    fn move_word_right(&mut self) {
+        if self.text_location.grapheme_idx >= self.buffer.borrow().grapheme_count(self.text_location.line_idx) {
+            self.move_right();
+        } else {
+            self.text_location.grapheme_idx = self.buffer.borrow().next_word_end(self.text_location);
+        }
+    }
+    fn move_to_start_of_file(&mut self) {
+        self.text_location = Location::default();
+    }
+    fn move_to_end_of_file(&mut self) {
+        self.text_location.line_idx = self.buffer.borrow().height().saturating_sub(1);
+        self.move_to_end_of_line();
+    }
+    // Paragraph movement skips blank lines next to the caret, then the paragraph, and stops on the blank line beyond it
+    fn move_to_previous_paragraph(&mut self) {
+        let mut line_idx = self.text_location.line_idx.saturating_sub(1);
+        while line_idx > 0 && self.buffer.borrow().is_blank(line_idx) {
+            line_idx = line_idx.saturating_sub(1);
+        }
+        while line_idx > 0 && !self.buffer.borrow().is_blank(line_idx) {
+            line_idx = line_idx.saturating_sub(1);
+        }
+        self.text_location = Location {
+            grapheme_idx: 0,
+            line_idx,
+        };
+    }
+    fn move_to_next_paragraph(&mut self) {
+        let height = self.buffer.borrow().height();
+        let mut line_idx = self.text_location.line_idx.saturating_add(1);
+        while line_idx < height && self.buffer.borrow().is_blank(line_idx) {
+            line_idx = line_idx.saturating_add(1);
+        }
+        while line_idx < height && !self.buffer.borrow().is_blank(line_idx) {
+            line_idx = line_idx.saturating_add(1);
+        }
+        if line_idx >= height {
+            self.move_to_end_of_file(); //no blank line left, stop at the end of the document
+        } else {
+            self.text_location = Location {
+                grapheme_idx: 0,
+                line_idx,
+            };
+        }
+    }
     fn move_to_start_of_line(&mut self) {
         self.text_location.grapheme_idx = 0;
     }
     fn move_to_end_of_line(&mut self) {
-           self.text_location.grapheme_idx = self.buffer.grapheme_count(self.text_location.line_idx);
+           self.text_location.grapheme_idx = self.buffer.borrow().grapheme_count(self.text_location.line_idx);
     }
 
     // Ensures self.location.grapheme_index points to a valid grapheme index by snapping it to the left most grapheme if appropriate.
@@ -361,7 +1143,7 @@ impl View {
     fn snap_to_valid_grapheme(&mut self) {
             self.text_location.grapheme_idx = min(
             self.text_location.grapheme_idx,
-            self.buffer.grapheme_count(self.text_location.line_idx),
+            self.buffer.borrow().grapheme_count(self.text_location.line_idx),
         
         );
     }
@@ -370,13 +1152,13 @@ impl View {
     // Doesn't trigger scrolling.
 
     fn snap_to_valid_line(&mut self) {
-        let last_idx = self.buffer.height();
+        let last_idx = self.buffer.borrow().height();
         self.text_location.line_idx = min(self.text_location.line_idx, last_idx);
     }
     //end region
 
     fn draw_symbol_fn() -> &'static str {
-        "⚡"
+        &Config::get().filler
     }
 }
 
@@ -390,53 +1172,73 @@ impl UIComponent for View {
 
     fn set_size(&mut self, size: Size) {
         self.size = size;
+        if self.soft_wrap {
+            //the top line may have fewer rows at the new width
+            let last_row_idx = self.wrap(self.scroll_offset.row).len().saturating_sub(1);
+            self.top_wrapped_row = min(self.top_wrapped_row, last_row_idx);
+        }
         self.scroll_text_location_into_view();
     }
 
-    fn draw(&mut self, origin_row: RowIdx) -> Result<(), Error> {
+    fn draw(&mut self, origin: Position) -> Result<(), Error> {
         let Size { height, width } = self.size;
-        let end_y = origin_row.saturating_add(height);
+        let gutter_width = self.gutter_width();
         //allow this as we dont care welcome msg is put in perfect posn
 
-        #[allow(clippy::integer_division)]
-        let bottom_third = 2 * height.div_ceil(3);
-        let scroll_top = self.scroll_offset.row;
+        let bottom_third = height.div_ceil(3).saturating_mul(2);
 
         let query = self
             .search_info
             .as_ref()
-            .and_then(|search_info| search_info.query.as_deref());
+            .and_then(|search_info| search_info.query.as_ref());
         let selected_match= query.is_some().then_some(self.text_location);
         let mut highlighter= Highlighter::new(
             query,
             selected_match,
-            self.buffer.get_file_info().get_file_type(),
+            self.selection(),
         );
 
-         for current_row in 0..end_y.saturating_add(scroll_top) {
-            self.buffer.highlight(current_row, &mut highlighter); 
-            //highlight from the start of the document to the end of the visible area, to ensure all annotations are up to date.
+        let visible_rows = self.visible_rows();
+        if let Some(last) = visible_rows.last() {
+            self.buffer.borrow_mut().highlight_syntax(last.line_idx);
+        }
+        let buffer = self.buffer.borrow();
+        //only the visible part of each visible line is highlighted, syntax highlighting is cached by the buffer
+        for rows in visible_rows.chunk_by(|row, next| row.line_idx == next.line_idx) {
+            if let (Some(first), Some(last)) = (rows.first(), rows.last()) {
+                buffer.highlight(first.line_idx, first.columns.start..last.columns.end, &mut highlighter);
+            }
         }
 
-        for current_row in origin_row..end_y {
-            //line_idx , take current(abs) row, subtract origin_row for row relative to view and add to scroll.offset
-
-            let line_idx = current_row
-                .saturating_sub(origin_row)
-                .saturating_add(scroll_top);
-            let left = self.scroll_offset.col;
-            let right = self.scroll_offset.col.saturating_add(width);
-            if let Some(annotated_string) = 
-                self.buffer
-                    .get_highlighted_substring(line_idx, left..right, &highlighter)
+        let mut visible_rows = visible_rows.into_iter();
+        for current_row in 0..height {
+            let at = Position {
+                row: origin.row.saturating_add(current_row),
+                col: origin.col,
+            };
+            //rows are handed out top to bottom, once the document ends the remaining rows are filled
+            if let Some(visual_row) = visible_rows.next()
+                && let Some(mut annotated_string) = buffer.get_highlighted_substring(
+                    visual_row.line_idx,
+                    visual_row.columns.clone(),
+                    &highlighter,
+                )
                     {
-                        Terminal::print_annotated_row(current_row, &annotated_string)?;
+                        if gutter_width > 0 {
+                            let annotation_type = if visual_row.line_idx == self.text_location.line_idx {
+                                AnnotationType::CurrentLineNumber
+                            } else {
+                                AnnotationType::LineNumber
+                            };
+                            annotated_string.prepend(&self.build_gutter(&visual_row, gutter_width), annotation_type);
+                        }
+                        Terminal::print_annotated_row(at, width, &annotated_string);
                     
-            } else if current_row == bottom_third && self.buffer.is_empty() {
-                Self::render_line(current_row, &Self::build_welcome_message(width))?;
+            } else if current_row == bottom_third && buffer.is_empty() {
+                self.render_line(at, &Self::build_welcome_message(width));
             } else {
                 let draw_symbol = Self::draw_symbol_fn();
-                Self::render_line(current_row, draw_symbol)?;
+                self.render_line(at, draw_symbol);
             }
         }
         Ok(())
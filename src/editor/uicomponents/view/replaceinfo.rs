@@ -0,0 +1,9 @@
+use crate::editor::Line;
+use crate::prelude::*;
+
+pub struct ReplaceInfo {
+    pub replacement: Line,
+    pub start: Location, // first candidate, replacing ends once the search wraps around to it
+    pub wrapped: bool,
+    pub replacements: usize,
+}
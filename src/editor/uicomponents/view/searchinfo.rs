@@ -1,8 +1,9 @@
-use crate::editor::Line;
+use crate::editor::SearchQuery;
 use crate::prelude::*;
 
 pub struct SearchInfo {
     pub prev_location: Location,
     pub prev_scroll_offset: Position,
-    pub query: Option<Line>,
+    pub prev_top_wrapped_row: RowIdx,
+    pub query: Option<SearchQuery>,
 }
\ No newline at end of file
@@ -0,0 +1,9 @@
+use crate::prelude::*;
+use std::ops::Range;
+
+// A screen row of the view, showing (part of) a line of the buffer
+pub struct VisualRow {
+    pub line_idx: LineIdx,
+    pub columns: Range<ColIdx>,
+    pub is_continuation: bool, // true for all but the first row of a soft wrapped line
+}
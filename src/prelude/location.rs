@@ -1,7 +1,23 @@
 use super::{GraphemeIdx, LineIdx};
+use std::cmp::Ordering;
 
-#[derive(Copy, Clone, Default)]
+#[derive(Copy, Clone, Default, PartialEq, Eq)]
 pub struct Location {
     pub grapheme_idx: GraphemeIdx,
     pub line_idx: LineIdx,
-}
\ No newline at end of file
+}
+
+impl Ord for Location {
+    fn cmp(&self, other: &Self) -> Ordering {
+        //locations are ordered as they appear in the document: by line first, then within the line
+        self.line_idx
+            .cmp(&other.line_idx)
+            .then(self.grapheme_idx.cmp(&other.grapheme_idx))
+    }
+}
+
+impl PartialOrd for Location {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
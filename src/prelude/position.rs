@@ -5,6 +5,12 @@ pub struct Position{
     pub row: RowIdx,
 }
 impl Position{
+    pub const fn saturating_add(self, other: Self)->Self{
+        Self{
+            row: self.row.saturating_add(other.row),
+            col: self.col.saturating_add(other.col)
+        }
+    }
     pub const fn saturating_sub(self, other: Self)->Self{
         Self{
             row: self.row.saturating_sub(other.row),